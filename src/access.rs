@@ -18,12 +18,54 @@ limitations under the License.
 // * https://docs.atlassian.com/software/jira/docs/api/REST/latest/
 // * https://docs.atlassian.com/jira-software/REST/latest/
 
-use crate::errors::JiraQueryError;
-use crate::issue_model::{Issue, JqlResults};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::agile::AgileFields;
+use crate::cassette::{RecordingTransport, ReplayTransport, Transport};
+use crate::errors::{ErrorBody, JiraQueryError};
+use crate::issue_model::{
+    Attachment, ChangelogPage, Comment, Comments, FieldMeta, FilterColumn, Issue, IssueType,
+    JqlResults, PartialIssue, PartialResults, ProjectIssueTypes, User, WatchesResults,
+};
 
 // The prefix of every subsequent REST request.
 // This string comes directly after the host in the URL.
 const REST_PREFIX: &str = "rest/api/2";
+// The version 3 REST API of Jira Cloud, which represents rich text in ADF.
+const REST_V3_PREFIX: &str = "rest/api/3";
+
+// The number of results on a page of a search that doesn't set `maxResults`,
+// which is the default of Jira Server and Jira Cloud.
+const DEFAULT_PAGE_SIZE: u32 = 50;
+
+// The number of issue keys in a single narrow search request,
+// which stays below the `maxResults` limit of Jira Cloud.
+const WATCHES_CHUNK_SIZE: usize = 50;
+
+// The delay before the first retry of a request without a `Retry-After` header.
+// Each further retry doubles the delay, up to the maximum.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+// The longest interval between rate-limited requests: one request per day.
+const RATE_LIMIT_MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+// The number of characters of a response body that an error includes.
+const MAX_ERROR_BODY_CHARS: usize = 1000;
+
+// The longest time that a request can take, unless the application sets its own timeout.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The user agent that identifies this library, unless the application sets its own.
+const DEFAULT_USER_AGENT: &str = concat!("jira_query/", env!("CARGO_PKG_VERSION"));
 
 /// Configuration and credentials to access a Jira instance.
 pub struct JiraInstance {
@@ -31,13 +73,190 @@ pub struct JiraInstance {
     pub auth: Auth,
     pub pagination: Pagination,
     client: reqwest::Client,
+    // The options of the client that the instance built itself,
+    // or `None` if the application provided the client.
+    http_options: Option<HttpOptions>,
+    rate_limiter: Option<RateLimiter>,
+    max_retries: u32,
+    retry_non_idempotent: bool,
+    default_fields: Option<Vec<String>>,
+    transport: Transport,
+    timeout: Option<Duration>,
+    pub(crate) agile_fields: AgileFields,
+}
+
+/// Whether the text looks like an issue key or an ID, which is safe to place in a query
+/// as is, such as `TEST-12` or `10001`.
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Whether both URLs have the same scheme, host, and port.
+/// A URL that doesn't parse has no origin in common with any other.
+fn same_origin(first: &str, second: &str) -> bool {
+    match (reqwest::Url::parse(first), reqwest::Url::parse(second)) {
+        (Ok(first), Ok(second)) => first.origin().is_tuple() && first.origin() == second.origin(),
+        _ => false,
+    }
+}
+
+/// Whether the response status is a temporary condition that a retry can overcome.
+fn is_transient(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// The time to wait before the next retry: the number of seconds in the `Retry-After`
+/// header, if any, and otherwise an exponential backoff by the number of previous retries.
+/// Either way, the delay is at most `RETRY_MAX_DELAY`, so that a bogus header
+/// can't stall the requests for hours.
+fn retry_delay(response: &reqwest::Response, retries: u32) -> Duration {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map_or_else(
+            || RETRY_BASE_DELAY.saturating_mul(2_u32.saturating_pow(retries)),
+            Duration::from_secs,
+        )
+        .min(RETRY_MAX_DELAY)
+}
+
+/// Tuning options of the underlying HTTP client.
+/// The client is rebuilt whenever one of them changes.
+#[derive(Clone, Debug, Default)]
+struct HttpOptions {
+    pool_max_idle_per_host: Option<usize>,
+    http2_prior_knowledge: bool,
+    tcp_keepalive: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+}
+
+impl HttpOptions {
+    /// Build an HTTP client that applies these options.
+    fn build_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        let user_agent = self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let mut builder = reqwest::Client::builder()
+            .tcp_keepalive(self.tcp_keepalive)
+            .user_agent(user_agent);
+
+        if let Some(max_idle) = self.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_idle);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+
+        builder.build()
+    }
+}
+
+/// A token bucket with the capacity of a single request, which spaces out
+/// all requests of the instance evenly, including concurrent ones.
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    // The earliest time when the next request can start.
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Space out the requests by the inverse of the rate, but at most by
+    /// `RATE_LIMIT_MAX_INTERVAL`, which also keeps the interval representable.
+    fn new(requests_per_second: f64) -> Self {
+        let seconds = 1.0 / requests_per_second;
+        let interval = if seconds < RATE_LIMIT_MAX_INTERVAL.as_secs_f64() {
+            Duration::from_secs_f64(seconds)
+        } else {
+            RATE_LIMIT_MAX_INTERVAL
+        };
+
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait until the next request can start without exceeding the rate.
+    async fn acquire(&self) {
+        let wait = {
+            // A poisoned lock still holds a valid time, so keep using it.
+            let mut next = self
+                .next
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + self.interval;
+            start - now
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Turn an error status of the response into the matching error.
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, JiraQueryError> {
+    let status = response.status();
+
+    match status {
+        reqwest::StatusCode::UNAUTHORIZED => Err(JiraQueryError::Unauthorized),
+        reqwest::StatusCode::FORBIDDEN => Err(JiraQueryError::Forbidden),
+        _ if status.is_client_error() || status.is_server_error() => {
+            // The body usually explains the error, but reading it can fail as well.
+            let body = response.text().await.unwrap_or_default();
+            Err(JiraQueryError::Http {
+                status: status.as_u16(),
+                body: truncate_body(&body),
+            })
+        }
+        _ => Ok(response),
+    }
+}
+
+/// Parse the JSON body of the response as the specified type. If the body doesn't match
+/// the type, the error includes the start of the body for debugging.
+pub(crate) async fn parse_json<T: DeserializeOwned>(
+    response: reqwest::Response,
+) -> Result<T, JiraQueryError> {
+    let body = response.bytes().await?;
+
+    serde_json::from_slice(&body).map_err(|source| JiraQueryError::Deserialize {
+        source,
+        body: truncate_body(&String::from_utf8_lossy(&body)),
+    })
+}
+
+/// Shorten a response body to a length that's practical in an error message.
+fn truncate_body(body: &str) -> String {
+    match body.char_indices().nth(MAX_ERROR_BODY_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.to_string(),
+    }
 }
 
 /// The authentication method used to contact Jira.
 pub enum Auth {
+    /// Send no credentials.
     Anonymous,
+    /// Send the token in the `Authorization: Bearer <token>` header.
+    /// Use this variant for the personal access tokens of Jira Data Center and Server.
     ApiKey(String),
+    /// Send the user name and the password, or the API token on Jira Cloud,
+    /// as HTTP basic authentication.
     Basic { user: String, password: String },
+    /// Send the value in a header with this name, such as `X-Jira-Token`,
+    /// for gateways that expect the token outside the `Authorization` header.
+    Custom { header_name: String, value: String },
 }
 
 // We could set a default enum variant and derive, but that raises the MSRV to 1.62.
@@ -51,10 +270,10 @@ impl Default for Auth {
 ///
 /// * `Default`: Use the default settings of this instance, which sets an arbitrary limit on the number of tickets.
 /// * `MaxResults`: Set the upper limit to this value. Note that each instance has a maximum allowed value,
-/// and if you set `MaxResults` higher than that, the instance uses its own maximum allowed value.
+///   and if you set `MaxResults` higher than that, the instance uses its own maximum allowed value.
 /// * `ChunkSize`: Access the tickets in a series of requests, each accessing the number of tickets equal to the chunk size.
-/// This enables you to access an unlimited number of tickets, as long as the chunk size is smaller
-/// than the maximum allowed results size for the instance.
+///   This enables you to access an unlimited number of tickets, as long as the chunk size is smaller
+///   than the maximum allowed results size for the instance.
 pub enum Pagination {
     Default,
     MaxResults(u32),
@@ -68,15 +287,200 @@ impl Default for Pagination {
     }
 }
 
+/// A field by which `JiraInstance::count_by` groups the issues.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupField {
+    Status,
+    Priority,
+    Assignee,
+    IssueType,
+}
+
+impl GroupField {
+    /// The ID of the field in the REST API.
+    const fn field_id(self) -> &'static str {
+        match self {
+            Self::Status => "status",
+            Self::Priority => "priority",
+            Self::Assignee => "assignee",
+            Self::IssueType => "issuetype",
+        }
+    }
+
+    /// The name of the group that the issue belongs to, or an empty string
+    /// if the field has no value.
+    fn group_of(self, issue: &PartialIssue) -> String {
+        let attribute = match self {
+            Self::Assignee => "displayName",
+            Self::Status | Self::Priority | Self::IssueType => "name",
+        };
+
+        issue
+            .fields
+            .get(self.field_id())
+            .and_then(|value| value.get(attribute))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    }
+}
+
+/// Additional options of a JQL search, which apply on top of the configuration
+/// of the `JiraInstance`. The default options don't modify the search.
+#[derive(Clone, Debug, Default)]
+pub struct SearchOptions {
+    fields_by_keys: bool,
+    // Restricts the fields in the response. The full `Issue` model needs the standard fields,
+    // so a narrower list only suits the methods that parse a narrower response.
+    fields: Option<Vec<String>>,
+    // Overrides the page size of the instance pagination for a single request.
+    max_results: Option<u32>,
+    properties: Option<Vec<String>>,
+    validate_query: Option<ValidationLevel>,
+    // The values of the `expand` parameter, such as `changelog` or `names`.
+    expand: BTreeSet<&'static str>,
+}
+
+/// How strictly Jira validates the JQL query of a search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationLevel {
+    /// Reject a query with any problem, such as an unknown field or function.
+    /// This is the default of Jira.
+    Strict,
+    /// Run the query, but return warnings about the problems.
+    Warn,
+    /// Run the query without validating it.
+    None,
+}
+
+impl ValidationLevel {
+    /// The value of the `validateQuery` parameter.
+    const fn parameter(self) -> &'static str {
+        match self {
+            Self::Strict => "strict",
+            Self::Warn => "warn",
+            Self::None => "none",
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Create search options with the default values.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refer to custom fields by their keys rather than their `customfield_*` IDs,
+    /// both in the JQL query and in the fields that the search returns.
+    /// This option is only available on Jira Cloud.
+    #[must_use]
+    pub const fn fields_by_keys(mut self, enabled: bool) -> Self {
+        self.fields_by_keys = enabled;
+        self
+    }
+
+    /// Request only these fields in the search, overriding the default fields
+    /// of the instance, if any. See `JiraInstance::default_fields`.
+    #[must_use]
+    pub fn fields(mut self, fields: &[&str]) -> Self {
+        self.fields = Some(fields.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    /// Include the entity properties with these keys in each issue of the results,
+    /// in `Issue::properties`. This saves a separate request for the properties of each issue.
+    #[must_use]
+    pub fn properties(mut self, keys: &[&str]) -> Self {
+        self.properties = Some(keys.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    /// Include the change history of each issue in the results, in `Issue::changelog`.
+    /// Jira returns at most 100 history entries of each issue this way, and which ones
+    /// differs between Jira versions. To get the complete history of an issue,
+    /// use `JiraInstance::issue_with_changelog`.
+    #[must_use]
+    pub fn changelog(self, enabled: bool) -> Self {
+        self.expand_if(enabled, "changelog")
+    }
+
+    /// Include the display names of the fields in `JqlResults::names`, such as to find out
+    /// which custom field is `Story Points`. See `JiraInstance::search_page`.
+    #[must_use]
+    pub fn names(self, enabled: bool) -> Self {
+        self.expand_if(enabled, "names")
+    }
+
+    /// Include the types of the fields in `JqlResults::schema`. See `JiraInstance::search_page`.
+    #[must_use]
+    pub fn schema(self, enabled: bool) -> Self {
+        self.expand_if(enabled, "schema")
+    }
+
+    /// Add the value to the `expand` parameter, or remove it.
+    fn expand_if(mut self, enabled: bool, value: &'static str) -> Self {
+        if enabled {
+            self.expand.insert(value);
+        } else {
+            self.expand.remove(value);
+        }
+        self
+    }
+
+    /// Set how strictly Jira validates the query. Loosen the validation for queries
+    /// with functions that the strict parser doesn't recognize, such as from apps.
+    #[must_use]
+    pub const fn validate_query(mut self, level: ValidationLevel) -> Self {
+        self.validate_query = Some(level);
+        self
+    }
+
+    /// Override the page size of the instance pagination.
+    pub(crate) const fn max_results(mut self, max_results: u32) -> Self {
+        self.max_results = Some(max_results);
+        self
+    }
+
+    /// Format the options as URL query parameters, each starting with `&`.
+    /// The fields of the options take precedence over the default fields.
+    fn url_parameters(&self, default_fields: Option<&[String]>) -> String {
+        let mut parameters = String::new();
+
+        if self.fields_by_keys {
+            parameters.push_str("&fieldsByKeys=true");
+        }
+        if let Some(fields) = self.fields.as_deref().or(default_fields) {
+            parameters.push_str("&fields=");
+            parameters.push_str(&fields.join(","));
+        }
+        if let Some(properties) = &self.properties {
+            parameters.push_str("&properties=");
+            parameters.push_str(&properties.join(","));
+        }
+        if let Some(level) = self.validate_query {
+            parameters.push_str("&validateQuery=");
+            parameters.push_str(level.parameter());
+        }
+        if !self.expand.is_empty() {
+            let expand: Vec<&str> = self.expand.iter().copied().collect();
+            parameters.push_str("&expand=");
+            parameters.push_str(&expand.join(","));
+        }
+
+        parameters
+    }
+}
+
 /// The method of the request to Jira. Either request specific IDs,
 /// or use a free-form JQL search query.
-enum Method<'a> {
+pub(crate) enum Method<'a> {
     Key(&'a str),
     Keys(&'a [&'a str]),
     Search(&'a str),
 }
 
-impl<'a> Method<'a> {
+impl Method<'_> {
     fn url_fragment(&self) -> String {
         match self {
             Self::Key(id) => format!("issue/{id}"),
@@ -89,19 +493,57 @@ impl<'a> Method<'a> {
 impl JiraInstance {
     /// Create a new `BzInstance` struct using a host URL, with default values
     /// for all options.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the HTTP client can't be initialized, such as when the TLS backend is unavailable.
     pub fn at(host: String) -> Result<Self, JiraQueryError> {
         // TODO: This function takes host as a String, even though client is happy with &str.
         // The String is only used in the host struct attribute.
-        let client = reqwest::Client::new();
+        let http_options = HttpOptions::default();
+        let client = http_options.build_client()?;
 
         Ok(Self {
             host,
             client,
+            http_options: Some(http_options),
+            rate_limiter: None,
+            max_retries: 0,
+            retry_non_idempotent: false,
+            default_fields: None,
+            transport: Transport::default(),
+            timeout: Some(DEFAULT_TIMEOUT),
+            agile_fields: AgileFields::default(),
             auth: Auth::default(),
             pagination: Pagination::default(),
         })
     }
 
+    /// Create a new `JiraInstance` that sends its requests with an existing HTTP client,
+    /// such as a shared client with a proxy, custom TLS roots, or timeouts.
+    ///
+    /// The instance doesn't apply its own user agent or timeout to the client.
+    /// Configure the client itself instead: the HTTP tuning methods,
+    /// such as `JiraInstance::user_agent`, fail with `JiraQueryError::ExternalClient`,
+    /// because they would replace the client along with its proxy, TLS roots, and headers.
+    #[must_use]
+    pub fn with_client(host: String, client: reqwest::Client) -> Self {
+        Self {
+            host,
+            client,
+            http_options: None,
+            rate_limiter: None,
+            max_retries: 0,
+            retry_non_idempotent: false,
+            default_fields: None,
+            transport: Transport::default(),
+            timeout: None,
+            agile_fields: AgileFields::default(),
+            auth: Auth::default(),
+            pagination: Pagination::default(),
+        }
+    }
+
     /// Set the authentication method of this `JiraInstance`.
     #[must_use]
     pub fn authenticate(mut self, auth: Auth) -> Self {
@@ -116,171 +558,1413 @@ impl JiraInstance {
         self
     }
 
+    /// Keep the rate of all requests from this `JiraInstance` under the limit
+    /// by spacing them out evenly, including concurrent requests. The limit prevents
+    /// the `429 Too Many Requests` responses of the instance.
+    ///
+    /// By default, the requests aren't limited. A rate under one request per day
+    /// counts as one request per day.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of requests per second isn't a positive, finite number.
+    #[must_use]
+    pub fn rate_limit(mut self, requests_per_second: f64) -> Self {
+        assert!(
+            requests_per_second > 0.0 && requests_per_second.is_finite(),
+            "The rate limit must be a positive number of requests per second."
+        );
+        self.rate_limiter = Some(RateLimiter::new(requests_per_second));
+        self
+    }
+
+    /// Repeat a request up to this many times if Jira responds with a transient error:
+    /// `429 Too Many Requests` or a 5xx server error. Each retry waits for the time
+    /// in the `Retry-After` header of the response, or otherwise for an exponentially
+    /// growing delay. Other errors, such as 400, 401, or 404, fail immediately.
+    ///
+    /// Only idempotent requests repeat. A POST, such as the one that creates an issue,
+    /// is sent exactly once, because a repeated request might apply the change twice,
+    /// unless you enable `JiraInstance::retry_non_idempotent`.
+    ///
+    /// By default, requests don't repeat. If the last retry fails as well,
+    /// the request fails with `JiraQueryError::RetriesExhausted`.
+    #[must_use]
+    pub const fn with_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Repeat also the requests that aren't idempotent, such as POST, on a transient error.
+    /// A server error doesn't always mean that Jira rejected the change, so a repeated
+    /// request might, for example, create the same issue or comment twice.
+    ///
+    /// By default, such requests are sent exactly once. The option only takes effect
+    /// with `JiraInstance::with_retries`.
+    #[must_use]
+    pub const fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    /// Send the requests to Jira, and also record each exchange in the directory
+    /// of the transport, so that `JiraInstance::replay` can serve them later.
+    #[must_use]
+    pub fn record(mut self, transport: RecordingTransport) -> Self {
+        self.transport = Transport::Record(transport);
+        self
+    }
+
+    /// Answer every request with its recorded response from the directory of the transport,
+    /// without any network connection. A request without a recording fails
+    /// with `JiraQueryError::MissingRecording`.
+    #[must_use]
+    pub fn replay(mut self, transport: ReplayTransport) -> Self {
+        self.transport = Transport::Replay(transport);
+        self
+    }
+
+    /// Request only these fields in every search, such as `&["summary", "status"]`,
+    /// unless the search sets its own fields with `SearchOptions::fields`.
+    /// Searches for issues by key, such as `JiraInstance::issues`, use the fields as well.
+    ///
+    /// The `Issue` model requires the standard fields, such as `summary`, `status`,
+    /// and `project`, so keep them in the list, or use a special value such as `*navigable`.
+    #[must_use]
+    pub fn default_fields(mut self, fields: &[&str]) -> Self {
+        self.default_fields = Some(fields.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    /// Fail a request with `JiraQueryError::Timeout` if it takes longer than this,
+    /// from opening the connection until the end of the response body.
+    /// The default is 30 seconds, or no timeout with `JiraInstance::with_client`.
+    ///
+    /// Long searches with a large page size might need a longer timeout.
+    #[must_use]
+    pub const fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fail a request with `JiraQueryError::Timeout` if the connection to Jira
+    /// doesn't open within this time. By default, only the timeout of the whole request applies.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ExternalClient` if the instance uses a client
+    /// from `JiraInstance::with_client`.
+    /// Fails if the HTTP client can't be rebuilt with the new option.
+    pub fn connect_timeout(self, timeout: Duration) -> Result<Self, JiraQueryError> {
+        self.rebuild_client(|options| options.connect_timeout = Some(timeout))
+    }
+
+    /// Set the maximum number of idle connections per host that the HTTP client keeps open.
+    /// Useful when many concurrent requests target the same instance.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ExternalClient` if the instance uses a client
+    /// from `JiraInstance::with_client`.
+    /// Fails if the HTTP client can't be rebuilt with the new option.
+    pub fn pool_max_idle_per_host(self, max_idle: usize) -> Result<Self, JiraQueryError> {
+        self.rebuild_client(|options| options.pool_max_idle_per_host = Some(max_idle))
+    }
+
+    /// Only use HTTP/2 to contact the instance, without negotiating the protocol first.
+    /// The Jira server or its proxy must support HTTP/2.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ExternalClient` if the instance uses a client
+    /// from `JiraInstance::with_client`.
+    /// Fails if the HTTP client can't be rebuilt with the new option.
+    pub fn http2_prior_knowledge(self) -> Result<Self, JiraQueryError> {
+        self.rebuild_client(|options| options.http2_prior_knowledge = true)
+    }
+
+    /// Send TCP keep-alive probes on open connections at this interval.
+    /// Set `None` to disable the probes, which is the default.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ExternalClient` if the instance uses a client
+    /// from `JiraInstance::with_client`.
+    /// Fails if the HTTP client can't be rebuilt with the new option.
+    pub fn tcp_keepalive(self, interval: Option<Duration>) -> Result<Self, JiraQueryError> {
+        self.rebuild_client(|options| options.tcp_keepalive = interval)
+    }
+
+    /// Identify the application to Jira with this user agent string.
+    /// The default is `jira_query/{version}`.
+    ///
+    /// Some Jira administrators filter or rate-limit requests by the user agent.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ExternalClient` if the instance uses a client
+    /// from `JiraInstance::with_client`.
+    /// Fails if the HTTP client can't be rebuilt with the new option,
+    /// such as when the user agent isn't a valid header value.
+    pub fn user_agent(self, user_agent: &str) -> Result<Self, JiraQueryError> {
+        self.rebuild_client(|options| options.user_agent = Some(user_agent.to_string()))
+    }
+
+    /// Change an option of the HTTP client that the instance built, and build it again.
+    fn rebuild_client(
+        mut self,
+        change: impl FnOnce(&mut HttpOptions),
+    ) -> Result<Self, JiraQueryError> {
+        let options = self
+            .http_options
+            .as_mut()
+            .ok_or(JiraQueryError::ExternalClient)?;
+        change(options);
+        self.client = options.build_client()?;
+        Ok(self)
+    }
+
     /// Based on the request method, form a complete, absolute URL
     /// to download the tickets from the REST API.
     #[must_use]
-    fn path(&self, method: &Method, start_at: u32) -> String {
-        let max_results = match self.pagination {
-            Pagination::Default => String::new(),
+    fn path(&self, method: &Method, options: &SearchOptions, start_at: u32) -> String {
+        let max_results = match (options.max_results, &self.pagination) {
+            // The search options can override the page size for a single request.
+            (Some(n), _) => format!("&maxResults={n}"),
+            (None, Pagination::Default) => String::new(),
             // For both MaxResults and ChunkSIze, set the maxResults size to the value set in the variant.
             // The maxResults size is relevant for ChunkSize in that each chunk requires its own results
             // to be at least this large.
-            Pagination::MaxResults(n) | Pagination::ChunkSize(n) => format!("&maxResults={n}"),
+            (None, Pagination::MaxResults(n) | Pagination::ChunkSize(n)) => {
+                format!("&maxResults={n}")
+            }
         };
 
         // The `startAt` option is only valid with JQL. With a URL by key, it breaks the REST query.
-        let start_at = match method {
-            Method::Key(_) => String::new(),
-            Method::Keys(_) | Method::Search(_) => format!("&startAt={start_at}"),
+        // The same applies to the search options.
+        let (start_at, options) = match method {
+            Method::Key(_) => (String::new(), String::new()),
+            Method::Keys(_) | Method::Search(_) => (
+                format!("&startAt={start_at}"),
+                options.url_parameters(self.default_fields.as_deref()),
+            ),
         };
 
         format!(
-            "{}/{}/{}{}{}",
+            "{}/{}/{}{}{}{}",
             self.host,
             REST_PREFIX,
             method.url_fragment(),
             max_results,
             start_at,
+            options,
         )
     }
 
-    /// Download the specified URL using the configured authentication.
-    async fn authenticated_get(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
-        let request_builder = self.client.get(url);
-        let authenticated = match &self.auth {
-            Auth::Anonymous => request_builder,
-            Auth::ApiKey(key) => request_builder.header("Authorization", &format!("Bearer {key}")),
-            Auth::Basic { user, password } => request_builder.basic_auth(user, Some(password)),
-        };
-        authenticated.send().await
+    /// Form a complete, absolute URL to a REST endpoint other than the issue access methods.
+    pub(crate) fn rest_url(&self, fragment: &str) -> String {
+        format!("{}/{}/{}", self.host, REST_PREFIX, fragment)
     }
 
-    // This method uses a separate implementation from `issues` because Jira provides a way
-    // to request a single ticket specifically. That conveniently handles error cases
-    // where no tickets might match, or more than one might.
-    /// Access a single issue by its key.
-    pub async fn issue(&self, key: &str) -> Result<Issue, JiraQueryError> {
-        let url = self.path(&Method::Key(key), 0);
-
-        // Gets an issue by ID and deserializes the JSON to data variable
-        let issue = self.authenticated_get(&url).await?.json::<Issue>().await?;
-
-        log::debug!("{:#?}", issue);
-
-        Ok(issue)
+    /// Form a complete, absolute URL to an endpoint of the version 3 REST API,
+    /// which only Jira Cloud provides.
+    pub(crate) fn rest_v3_url(&self, fragment: &str) -> String {
+        format!("{}/{}/{}", self.host, REST_V3_PREFIX, fragment)
     }
 
-    /// Access several issues by their keys.
-    ///
-    /// If the list of keys is empty, returns an empty list back with no errors.
-    pub async fn issues(&self, keys: &[&str]) -> Result<Vec<Issue>, JiraQueryError> {
-        // If the user specifies no keys, skip network requests and return no bugs.
-        // Returning an error could also be valid, but I believe that this behavior
-        // is less surprising and more practical.
-        if keys.is_empty() {
-            return Ok(Vec::new());
+    /// Prepare a request to the specified URL without any authentication.
+    fn anonymous_request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request_builder = self.client.request(method, url);
+        match self.timeout {
+            Some(timeout) => request_builder.timeout(timeout),
+            None => request_builder,
         }
+    }
 
-        let method = Method::Keys(keys);
-
-        // If Pagination is set to ChunkSize, split the issue keys into chunk by chunk size
-        // and request each chunk separately.
-        if let Pagination::ChunkSize(chunk_size) = self.pagination {
-            self.paginated_issues(&method, chunk_size).await
-        // If Pagination is not set to ChunkSize, use a single chunk request for all issues.
+    /// Prepare a GET request to a URL from the Jira data, such as the URL of an icon.
+    /// The request only carries the configured authentication if the URL has the same
+    /// scheme, host, and port as the instance, so that the credentials don't leak
+    /// to another server.
+    pub(crate) fn request_on_instance(&self, url: &str) -> reqwest::RequestBuilder {
+        if same_origin(url, &self.host) {
+            self.authenticated_request(reqwest::Method::GET, url)
         } else {
-            let issues = self.chunk_of_issues(&method, 0).await?;
+            log::debug!("Requesting {url} without credentials, outside the Jira instance.");
+            self.anonymous_request(reqwest::Method::GET, url)
+        }
+    }
 
-            // If the resulting list is empty, return an error.
-            // TODO: The REST parsing above already results in an error if the results are empty.
-            // Try to catch the error there.
-            if issues.is_empty() {
-                Err(JiraQueryError::NoIssues)
-            } else {
-                Ok(issues)
+    /// Prepare a request to the specified URL using the configured authentication.
+    pub(crate) fn authenticated_request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> reqwest::RequestBuilder {
+        let request_builder = self.anonymous_request(method, url);
+        match &self.auth {
+            Auth::Anonymous => request_builder,
+            Auth::ApiKey(key) => request_builder.header("Authorization", &format!("Bearer {key}")),
+            Auth::Basic { user, password } => request_builder.basic_auth(user, Some(password)),
+            Auth::Custom { header_name, value } => {
+                request_builder.header(header_name.as_str(), value.as_str())
             }
         }
     }
 
-    /// Download all issues specified in the request as a series of chunks or pages.
-    /// The request controls whether the download works with IDs or JQL.
-    /// This function only processes the resulting pages coming back from Jira
-    /// and stops the iteration at the last page.
+    /// Send the request and turn an error status of the response into the matching error.
     ///
-    /// See the Jira documentation:
-    /// <https://confluence.atlassian.com/jirakb/changing-maxresults-parameter-for-jira-rest-api-779160706.html>.
-    async fn paginated_issues(
+    /// With retries enabled, idempotent requests repeat on transient errors.
+    /// Requests that aren't idempotent, such as the POST that creates an issue,
+    /// are sent exactly once, because a repeated request might apply the change twice.
+    pub(crate) async fn send(
         &self,
-        method: &Method<'_>,
-        chunk_size: u32,
-    ) -> Result<Vec<Issue>, JiraQueryError> {
-        let mut all_issues = Vec::new();
-        let mut start_at = 0;
-
-        loop {
-            let mut chunk_issues = self.chunk_of_issues(method, start_at).await?;
-            // Calculate the length now before the content moves to `all_issues`.
-            let page_size = chunk_issues.len();
-            all_issues.append(&mut chunk_issues);
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, JiraQueryError> {
+        let response = self.dispatch(request).await?;
+        check_status(response).await
+    }
 
-            // If this page contains fewer issues than the chunk size,
-            // it's the last page. Stop the loop.
-            if page_size < chunk_size as usize {
-                break;
-            }
+    /// Send the request like `send`, but report a 404 response as a missing issue
+    /// or project with this key, based on the messages in the response.
+    pub(crate) async fn send_for_key(
+        &self,
+        request: reqwest::RequestBuilder,
+        key: &str,
+    ) -> Result<reqwest::Response, JiraQueryError> {
+        let response = self.dispatch(request).await?;
 
-            start_at += chunk_size;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            let body = response.json::<ErrorBody>().await.unwrap_or_default();
+            return Err(JiraQueryError::not_found(key, &body));
         }
 
-        Ok(all_issues)
+        check_status(response).await
     }
 
-    /// Download a specific list (chunk) of issues.
-    /// Reused elsewhere as a building block of different pagination methods.
-    async fn chunk_of_issues(
+    /// Send the request once the rate limit allows it, and repeat an idempotent request,
+    /// or any request if the instance allows it, on a transient error status,
+    /// as long as the instance allows more retries.
+    async fn dispatch(
         &self,
-        method: &Method<'_>,
-        start_at: u32,
-    ) -> Result<Vec<Issue>, JiraQueryError> {
-        let url = self.path(method, start_at);
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, JiraQueryError> {
+        let (client, request) = request.build_split();
+        let request = request?;
+        let mut retries = 0;
 
-        let results = self
-            .authenticated_get(&url)
+        loop {
+            // Keep the original request for the next attempt, unless this is the last one.
+            let repeatable = self.retry_non_idempotent || request.method().is_idempotent();
+            let copy = if retries < self.max_retries && repeatable {
+                request.try_clone()
+            } else {
+                None
+            };
+
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            if let Some(copy) = copy {
+                let response = self.transport.execute(&client, copy).await?;
+                if !is_transient(response.status()) {
+                    return Ok(response);
+                }
+
+                let delay = retry_delay(&response, retries);
+                log::warn!(
+                    "Jira responded with {}. Retrying in {delay:?}.",
+                    response.status()
+                );
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            } else {
+                let response = self.transport.execute(&client, request).await?;
+                let status = response.status();
+                if retries > 0 && is_transient(status) {
+                    return Err(JiraQueryError::RetriesExhausted {
+                        retries,
+                        status: status.as_u16(),
+                    });
+                }
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Download the specified URL using the configured authentication,
+    /// and parse the JSON response as the specified type.
+    pub(crate) async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<T, JiraQueryError> {
+        parse_json(self.authenticated_get(url).await?).await
+    }
+
+    /// Download the specified URL using the configured authentication.
+    pub(crate) async fn authenticated_get(
+        &self,
+        url: &str,
+    ) -> Result<reqwest::Response, JiraQueryError> {
+        self.send(self.authenticated_request(reqwest::Method::GET, url))
+            .await
+    }
+
+    // This method uses a separate implementation from `issues` because Jira provides a way
+    // to request a single ticket specifically. That conveniently handles error cases
+    // where no tickets might match, or more than one might.
+    /// Access a single issue by its key.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::NotFound` or `JiraQueryError::ProjectNotFound`
+    /// if Jira finds no such issue or project, or if the request to Jira fails
+    /// or the response doesn't match the issue model.
+    pub async fn issue(&self, key: &str) -> Result<Issue, JiraQueryError> {
+        let url = self.path(&Method::Key(key), &SearchOptions::default(), 0);
+        let request = self.authenticated_request(reqwest::Method::GET, &url);
+
+        // Gets an issue by ID and deserializes the JSON to data variable
+        let issue = parse_json::<Issue>(self.send_for_key(request, key).await?).await?;
+
+        log::debug!("{issue:#?}");
+
+        Ok(issue)
+    }
+
+    /// Access a single issue by its key, with only the selected fields, such as
+    /// `&["summary", "status"]`. The response is much smaller than the complete issue.
+    ///
+    /// The field names pass to Jira as they are, so you can also use the special
+    /// values that Jira supports, such as `*navigable` or `-comment`.
+    /// The request percent-encodes the field names.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidKey` if the key contains other characters than
+    /// letters, digits, `-`, and `_`, which could otherwise alter the URL.
+    /// Fails with `JiraQueryError::NotFound` or `JiraQueryError::ProjectNotFound`
+    /// if Jira finds no such issue or project, or if the request to Jira fails.
+    pub async fn issue_with_fields(
+        &self,
+        key: &str,
+        fields: &[&str],
+    ) -> Result<PartialIssue, JiraQueryError> {
+        if !is_plain_key(key) {
+            return Err(JiraQueryError::InvalidKey(key.to_string()));
+        }
+
+        let url = self.rest_url(&format!("issue/{key}"));
+        let request = self
+            .authenticated_request(reqwest::Method::GET, &url)
+            .query(&[("fields", fields.join(","))]);
+
+        let issue = parse_json::<PartialIssue>(self.send_for_key(request, key).await?).await?;
+
+        log::debug!("{issue:#?}");
+
+        Ok(issue)
+    }
+
+    /// Access a single issue by its key, including its change history in `Issue::changelog`,
+    /// such as to find out when and by whom the status changed.
+    /// If the expanded history is incomplete, the method downloads the complete history
+    /// from the changelog endpoint instead, from the oldest entry.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::NotFound` or `JiraQueryError::ProjectNotFound`
+    /// if Jira finds no such issue or project, or if the request to Jira fails
+    /// or the response doesn't match the issue model.
+    pub async fn issue_with_changelog(&self, key: &str) -> Result<Issue, JiraQueryError> {
+        let url = self.rest_url(&format!("issue/{key}?expand=changelog"));
+        let request = self.authenticated_request(reqwest::Method::GET, &url);
+
+        let mut issue = parse_json::<Issue>(self.send_for_key(request, key).await?).await?;
+
+        // The expansion only includes a part of a long history, and not necessarily
+        // the oldest entries, so continuing from its length could skip or repeat entries.
+        // Replace it with all pages of the changelog endpoint.
+        if let Some(changelog) = &mut issue.changelog {
+            if changelog.histories.len() < usize::try_from(changelog.total).unwrap_or_default() {
+                let mut histories = Vec::new();
+
+                loop {
+                    let url = self.rest_url(&format!(
+                        "issue/{key}/changelog?startAt={}",
+                        histories.len()
+                    ));
+                    let page = self.get_json::<ChangelogPage>(&url).await?;
+
+                    let last = page.is_last || page.values.is_empty();
+                    histories.extend(page.values);
+                    if last {
+                        break;
+                    }
+                }
+
+                changelog.start_at = 0;
+                changelog.max_results = i32::try_from(histories.len()).unwrap_or(i32::MAX);
+                changelog.histories = histories;
+            }
+        }
+
+        log::debug!("{issue:#?}");
+
+        Ok(issue)
+    }
+
+    /// Access several issues by their keys, or by their numeric IDs, in a search
+    /// per page of keys: the page size of the instance pagination, or 50 by default.
+    ///
+    /// The issues come back in the order of the requested keys. If the list of keys is empty,
+    /// returns an empty list back with no errors.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidKey` if a key contains other characters than
+    /// letters, digits, `-`, and `_`, which could otherwise alter the search query.
+    /// Fails with `JiraQueryError::Http` and the status 400 if any of the keys doesn't exist
+    /// or the user can't see the issue, because Jira rejects the whole search then.
+    /// Fails if the request to Jira fails, if the response doesn't match the issue model,
+    /// or if Jira returns no issues.
+    pub async fn issues(&self, keys: &[&str]) -> Result<Vec<Issue>, JiraQueryError> {
+        // If the user specifies no keys, skip network requests and return no bugs.
+        // Returning an error could also be valid, but I believe that this behavior
+        // is less surprising and more practical.
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if let Some(invalid) = keys.iter().find(|key| !is_plain_key(key)) {
+            return Err(JiraQueryError::InvalidKey((*invalid).to_string()));
+        }
+
+        let mut issues = self.issues_by_keys(keys).await?;
+
+        // The search returns the issues in its default order. Restore the requested order.
+        let positions: HashMap<&str, usize> = keys
+            .iter()
+            .enumerate()
+            .map(|(position, key)| (*key, position))
+            .collect();
+        issues.sort_by_key(|issue| {
+            positions
+                .get(issue.key.as_str())
+                .or_else(|| positions.get(issue.id.as_str()))
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+
+        Ok(issues)
+    }
+
+    /// Search for the issues with these keys, in the order that the search returns.
+    ///
+    /// The keys are split into chunks no larger than a page of results,
+    /// so that every issue fits on the single page of its chunk.
+    async fn issues_by_keys(&self, keys: &[&str]) -> Result<Vec<Issue>, JiraQueryError> {
+        let page_size = match self.pagination {
+            Pagination::Default => DEFAULT_PAGE_SIZE,
+            Pagination::MaxResults(n) | Pagination::ChunkSize(n) => n.max(1),
+        };
+        let options = SearchOptions::default();
+        let mut issues = Vec::new();
+
+        for chunk in keys.chunks(usize::try_from(page_size).unwrap_or(usize::MAX)) {
+            let method = Method::Keys(chunk);
+            issues.extend(self.chunk_of_issues(&method, &options, 0).await?.issues);
+        }
+
+        // If the resulting list is empty, return an error.
+        // TODO: The REST parsing above already results in an error if the results are empty.
+        // Try to catch the error there.
+        if issues.is_empty() {
+            Err(JiraQueryError::NoIssues)
+        } else {
+            Ok(issues)
+        }
+    }
+
+    /// Access an issue together with the issues linked to it, up to `depth` links away,
+    /// by their keys. Links in both directions count, and a `depth` of 0 only accesses the issue.
+    ///
+    /// Each level of links is downloaded in a single batch. Every issue is downloaded once,
+    /// even if the links form a cycle.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails, if the response doesn't match the issue model,
+    /// or if Jira returns none of the linked issues on a level, such as when the user
+    /// can't see them.
+    pub async fn issue_with_links(
+        &self,
+        key: &str,
+        depth: u8,
+    ) -> Result<HashMap<String, Issue>, JiraQueryError> {
+        let mut issues = HashMap::new();
+        let mut level = vec![self.issue(key).await?];
+
+        for hop in 0..=depth {
+            let linked: BTreeSet<String> = level
+                .iter()
+                .flat_map(|issue| &issue.fields.issuelinks)
+                .flat_map(|link| link.outward_issue.iter().chain(link.inward_issue.iter()))
+                .map(|linked| linked.key.clone())
+                .collect();
+
+            for issue in level {
+                issues.insert(issue.key.clone(), issue);
+            }
+
+            let unvisited: Vec<&str> = linked
+                .iter()
+                .map(String::as_str)
+                .filter(|key| !issues.contains_key(*key))
+                .collect();
+
+            if hop == depth || unvisited.is_empty() {
+                break;
+            }
+
+            level = self.issues(&unvisited).await?;
+        }
+
+        Ok(issues)
+    }
+
+    /// Access the ancestors of the issue by following its `parent` links up the hierarchy,
+    /// such as from a subtask to its story and then to the epic of the story.
+    ///
+    /// The result starts with the direct parent and ends with the top of the hierarchy.
+    /// It's empty if the issue has no parent. The walk stops early at a parent
+    /// that doesn't exist or that the user can't see, and at a cycle of parents.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails, except for a missing parent,
+    /// or if the response doesn't match the issue model.
+    pub async fn issue_ancestors(&self, key: &str) -> Result<Vec<Issue>, JiraQueryError> {
+        let mut ancestors: Vec<Issue> = Vec::new();
+        let mut visited = HashSet::from([key.to_string()]);
+        let mut parent_key = self
+            .issue(key)
+            .await?
+            .fields
+            .parent
+            .map(|parent| parent.key);
+
+        while let Some(current) = parent_key {
+            if !visited.insert(current.clone()) {
+                log::warn!("The parents of {key} form a cycle at {current}.");
+                break;
+            }
+
+            let issue = match self.issue(&current).await {
+                Err(JiraQueryError::NotFound { .. }) => {
+                    log::warn!("The parent issue {current} is missing.");
+                    break;
+                }
+                issue => issue?,
+            };
+
+            parent_key = issue
+                .fields
+                .parent
+                .as_ref()
+                .map(|parent| parent.key.clone());
+            ancestors.push(issue);
+        }
+
+        Ok(ancestors)
+    }
+
+    /// Download all issues specified in the request as a series of chunks or pages.
+    /// The request controls whether the download works with IDs or JQL.
+    /// This function only processes the resulting pages coming back from Jira
+    /// and stops the iteration at the last page.
+    ///
+    /// The issues might change on the server while the pages are downloading,
+    /// which shifts the positions of the remaining issues. To compensate:
+    ///
+    /// * If the `total` count drops between pages, the download steps back
+    ///   by the difference and requests the overlapping issues again.
+    /// * Issues that appear on more than one page are kept only once, by key.
+    ///
+    /// As a result, every issue that matches the request for the whole duration
+    /// of the download is returned at least once, and no key is returned twice.
+    ///
+    /// See the Jira documentation:
+    /// <https://confluence.atlassian.com/jirakb/changing-maxresults-parameter-for-jira-rest-api-779160706.html>.
+    async fn paginated_issues(
+        &self,
+        method: &Method<'_>,
+        options: &SearchOptions,
+        chunk_size: u32,
+    ) -> Result<Vec<Issue>, JiraQueryError> {
+        let mut all_issues = Vec::new();
+        self.paginate_into(method, options, chunk_size, |issue| {
+            all_issues.push(issue);
+            Ok(())
+        })
+        .await?;
+
+        Ok(all_issues)
+    }
+
+    /// Download all issues specified in the request page by page, like `paginated_issues`,
+    /// but pass each unique issue to the `sink` as soon as its page arrives,
+    /// rather than collecting them all in memory.
+    pub(crate) async fn paginate_into<F>(
+        &self,
+        method: &Method<'_>,
+        options: &SearchOptions,
+        chunk_size: u32,
+        mut sink: F,
+    ) -> Result<(), JiraQueryError>
+    where
+        F: FnMut(Issue) -> Result<(), JiraQueryError> + Send,
+    {
+        let mut tracker = PageTracker::default();
+        let mut start_at = 0;
+
+        loop {
+            let results = self.chunk_of_issues(method, options, start_at).await?;
+            let total = u64::try_from(results.total).ok();
+            // Calculate the length now before the content moves to the sink.
+            let page_size = results.issues.len();
+
+            for issue in results.issues {
+                if tracker.is_new(&issue) {
+                    sink(issue)?;
+                }
+            }
+
+            let shift = tracker.step_back(total);
+            if shift > 0 {
+                start_at = start_at.saturating_sub(shift);
+                continue;
+            }
+
+            // If this page contains fewer issues than the chunk size,
+            // it's the last page. Stop the loop.
+            if page_size < chunk_size as usize {
+                break;
+            }
+
+            start_at += chunk_size;
+        }
+
+        Ok(())
+    }
+
+    /// Download a specific list (chunk) of issues.
+    /// Reused elsewhere as a building block of different pagination methods.
+    async fn chunk_of_issues(
+        &self,
+        method: &Method<'_>,
+        options: &SearchOptions,
+        start_at: u32,
+    ) -> Result<JqlResults, JiraQueryError> {
+        self.chunk_of(method, options, start_at).await
+    }
+
+    /// Download a specific chunk of search results, parsed as the specified type.
+    /// Useful with search options that return a narrower response than the full issue model.
+    async fn chunk_of<T: DeserializeOwned + Debug>(
+        &self,
+        method: &Method<'_>,
+        options: &SearchOptions,
+        start_at: u32,
+    ) -> Result<T, JiraQueryError> {
+        let url = self.path(method, options, start_at);
+
+        let results = self.get_json::<T>(&url).await?;
+
+        log::debug!("{results:#?}");
+
+        Ok(results)
+    }
+
+    /// Check whether the current user is watching each of the issues,
+    /// keyed by the issue key. The method downloads only the `watches` field.
+    ///
+    /// If the list of keys is empty, returns an empty map with no errors.
+    /// Issues that don't exist or that the user can't see are missing from the map.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidKey` if a key contains other characters than
+    /// letters, digits, `-`, and `_`, which could otherwise alter the search query.
+    /// Fails if the request to Jira fails or if the response doesn't match the watches model.
+    pub async fn watching_status(
+        &self,
+        keys: &[&str],
+    ) -> Result<HashMap<String, bool>, JiraQueryError> {
+        if let Some(invalid) = keys.iter().find(|key| !is_plain_key(key)) {
+            return Err(JiraQueryError::InvalidKey((*invalid).to_string()));
+        }
+
+        let mut status = HashMap::new();
+
+        for chunk in keys.chunks(WATCHES_CHUNK_SIZE) {
+            let options = SearchOptions {
+                fields: Some(vec!["watches".to_string()]),
+                max_results: Some(u32::try_from(chunk.len()).unwrap_or(u32::MAX)),
+                ..SearchOptions::default()
+            };
+            let results: WatchesResults = self.chunk_of(&Method::Keys(chunk), &options, 0).await?;
+
+            for issue in results.issues {
+                status.insert(issue.key, issue.fields.watches.is_watching);
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Check that the Jira instance is reachable with the configured authentication,
+    /// and measure the round-trip time of a small request to the server information endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the instance is unreachable or if it rejects the request.
+    pub async fn ping(&self) -> Result<Duration, JiraQueryError> {
+        let url = self.rest_url("serverInfo");
+
+        let start = Instant::now();
+        self.authenticated_get(&url).await?;
+        let latency = start.elapsed();
+
+        log::debug!("Jira responded in {latency:?}.");
+
+        Ok(latency)
+    }
+
+    /// Download an icon from the Jira instance, such as the `icon_url` of a priority,
+    /// a status, or an issue type. Some instances require authentication for icons,
+    /// so the request uses the configured authentication.
+    ///
+    /// The result is the raw content of the image, which you can cache.
+    ///
+    /// Icons can also come from other servers, such as avatars from Gravatar.
+    /// The request only carries the credentials if the URL is on the host of the instance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if Jira responds with an error status.
+    pub async fn download_icon(&self, url: &str) -> Result<Bytes, JiraQueryError> {
+        let icon = self
+            .send(self.request_on_instance(url))
             .await?
-            .json::<JqlResults>()
+            .bytes()
             .await?;
 
-        log::debug!("{:#?}", results);
+        Ok(icon)
+    }
 
-        Ok(results.issues)
+    /// Download the content of a file attached to an issue, with the configured authentication.
+    ///
+    /// The request only carries the credentials if the content URL of the attachment
+    /// is on the host of the instance, because the URL comes from the Jira data.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if Jira responds with an error status.
+    pub async fn download_attachment(
+        &self,
+        attachment: &Attachment,
+    ) -> Result<Bytes, JiraQueryError> {
+        let content = self
+            .send(self.request_on_instance(&attachment.content))
+            .await?
+            .bytes()
+            .await?;
+
+        Ok(content)
+    }
+
+    /// Count the issues that match a free-form JQL search in groups by the field,
+    /// such as by status for a chart. The keys are the display names of the groups,
+    /// such as `In Progress` or `Jane Doe`.
+    ///
+    /// The search downloads only the one field, page by page, until it reaches
+    /// the total of the search. Issues without a value in the field,
+    /// such as unassigned issues, count under an empty string.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue model.
+    pub async fn count_by(
+        &self,
+        query: &str,
+        group_field: GroupField,
+    ) -> Result<HashMap<String, usize>, JiraQueryError> {
+        let method = Method::Search(query);
+        let options = SearchOptions {
+            fields: Some(vec![group_field.field_id().to_string()]),
+            ..SearchOptions::default()
+        };
+
+        let mut counts = HashMap::new();
+        let mut start_at = 0;
+
+        loop {
+            let results: PartialResults = self.chunk_of(&method, &options, start_at).await?;
+            let page_len = u32::try_from(results.issues.len()).unwrap_or(u32::MAX);
+
+            for issue in &results.issues {
+                *counts.entry(group_field.group_of(issue)).or_default() += 1;
+            }
+
+            start_at += page_len;
+            let total = u32::try_from(results.total).unwrap_or_default();
+            if page_len == 0 || start_at >= total {
+                break;
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Access only the first `n` issues that match a free-form JQL search.
+    ///
+    /// The method requests pages of at most the configured page size, and stops
+    /// as soon as it has `n` issues, without downloading the remaining pages.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue model.
+    pub async fn search_first_n(&self, query: &str, n: u32) -> Result<Vec<Issue>, JiraQueryError> {
+        let method = Method::Search(query);
+        let page_size = match self.pagination {
+            Pagination::MaxResults(size) | Pagination::ChunkSize(size) => size.min(n),
+            Pagination::Default => n,
+        };
+
+        let mut issues: Vec<Issue> = Vec::new();
+        let mut start_at = 0;
+
+        while issues.len() < n as usize {
+            let remaining = n - u32::try_from(issues.len()).unwrap_or(n);
+            let options = SearchOptions {
+                max_results: Some(remaining.min(page_size)),
+                ..SearchOptions::default()
+            };
+            let results = self.chunk_of_issues(&method, &options, start_at).await?;
+            let total = u64::try_from(results.total).ok();
+            let page_len = u32::try_from(results.issues.len()).unwrap_or(u32::MAX);
+
+            issues.extend(results.issues);
+            start_at += page_len;
+
+            // Stop when the search runs out of issues. The instance might return
+            // fewer issues than requested per page, so rely on the total if possible.
+            let exhausted = total.map_or(false, |total| u64::from(start_at) >= total);
+            if page_len == 0 || exhausted {
+                break;
+            }
+        }
+
+        issues.truncate(n as usize);
+        Ok(issues)
+    }
+
+    /// List all issue types on the Jira instance, such as for an issue type picker.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue type model.
+    pub async fn issue_types(&self) -> Result<Vec<IssueType>, JiraQueryError> {
+        let url = self.rest_url("issuetype");
+
+        let issue_types = self.get_json::<Vec<IssueType>>(&url).await?;
+
+        Ok(issue_types)
+    }
+
+    /// List the issue types available in the project. On Jira Cloud,
+    /// projects can use issue types that are only visible in their own scope.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::ProjectNotFound` if Jira finds no such project,
+    /// or if the request to Jira fails or the response doesn't match the issue type model.
+    pub async fn project_issue_types(
+        &self,
+        project_key: &str,
+    ) -> Result<Vec<IssueType>, JiraQueryError> {
+        let url = self.rest_url(&format!("project/{project_key}"));
+        let request = self.authenticated_request(reqwest::Method::GET, &url);
+
+        let project =
+            parse_json::<ProjectIssueTypes>(self.send_for_key(request, project_key).await?).await?;
+
+        Ok(project.issue_types)
+    }
+
+    /// List the users who can be assigned to the issue or to issues in the project,
+    /// such as for an assignee picker.
+    ///
+    /// The argument is either an issue key, such as `TEST-1`, or a project key, such as `TEST`.
+    /// A key that ends with a dash and a number is an issue key.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidKey` if the key contains other characters than
+    /// letters, digits, `-`, and `_`, which could otherwise alter the query.
+    /// Fails if the request to Jira fails or if the response doesn't match the user model.
+    pub async fn assignable_users(
+        &self,
+        project_or_issue: &str,
+    ) -> Result<Vec<User>, JiraQueryError> {
+        if !is_plain_key(project_or_issue) {
+            return Err(JiraQueryError::InvalidKey(project_or_issue.to_string()));
+        }
+
+        let is_issue_key = project_or_issue
+            .rsplit_once('-')
+            .map_or(false, |(project, number)| {
+                !project.is_empty()
+                    && !number.is_empty()
+                    && number.chars().all(|c| c.is_ascii_digit())
+            });
+        let parameter = if is_issue_key { "issueKey" } else { "project" };
+        let url = self.rest_url(&format!(
+            "user/assignable/search?{parameter}={project_or_issue}"
+        ));
+
+        let users = self.get_json::<Vec<User>>(&url).await?;
+
+        Ok(users)
+    }
+
+    /// List the metadata of all system and custom fields on the Jira instance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the field model.
+    pub async fn field_metadata(&self) -> Result<Vec<FieldMeta>, JiraQueryError> {
+        let url = self.rest_url("field");
+
+        let fields = self.get_json::<Vec<FieldMeta>>(&url).await?;
+
+        Ok(fields)
+    }
+
+    /// Access a single comment of an issue by its ID, such as the ID
+    /// that a webhook reports for a new comment.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::MissingComment` if Jira finds no such comment,
+    /// or if the request to Jira fails or the response doesn't match the comment model.
+    pub async fn comment(
+        &self,
+        issue_key: &str,
+        comment_id: &str,
+    ) -> Result<Comment, JiraQueryError> {
+        let url = self.rest_url(&format!("issue/{issue_key}/comment/{comment_id}"));
+
+        let response = match self.authenticated_get(&url).await {
+            Err(JiraQueryError::Http { status: 404, .. }) => {
+                return Err(JiraQueryError::MissingComment {
+                    issue: issue_key.to_string(),
+                    comment: comment_id.to_string(),
+                });
+            }
+            response => response?,
+        };
+
+        let comment = parse_json::<Comment>(response).await?;
+
+        Ok(comment)
+    }
+
+    /// Access all comments of the issue, from the oldest, following the pages
+    /// of the comment endpoint. Unlike the `comment` field of an issue,
+    /// which only holds the first page, the list includes every comment.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::NotFound` or `JiraQueryError::ProjectNotFound`
+    /// if Jira finds no such issue or project, or if the request to Jira fails
+    /// or the response doesn't match the comment model.
+    pub async fn comments(&self, key: &str) -> Result<Vec<Comment>, JiraQueryError> {
+        let mut comments = Vec::new();
+
+        loop {
+            let url = self.rest_url(&format!(
+                "issue/{key}/comment?orderBy=created&startAt={}",
+                comments.len()
+            ));
+            let request = self.authenticated_request(reqwest::Method::GET, &url);
+            let page = parse_json::<Comments>(self.send_for_key(request, key).await?).await?;
+
+            let start_at = usize::try_from(page.start_at).unwrap_or_default();
+            let total = usize::try_from(page.total).unwrap_or_default();
+            let done = page.comments.is_empty() || start_at + page.comments.len() >= total;
+            comments.extend(page.comments);
+            if done {
+                break;
+            }
+        }
+
+        log::debug!("{comments:#?}");
+
+        Ok(comments)
+    }
+
+    /// List the issue navigator columns configured for a saved filter,
+    /// which are the columns that the filter shows in the Jira UI.
+    ///
+    /// Use the columns with `Issue::column_values` to export issues with the same columns.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the column model.
+    pub async fn filter_columns(
+        &self,
+        filter_id: &str,
+    ) -> Result<Vec<FilterColumn>, JiraQueryError> {
+        let url = self.rest_url(&format!("filter/{filter_id}/columns"));
+
+        let columns = self.get_json::<Vec<FilterColumn>>(&url).await?;
+
+        Ok(columns)
     }
 
     /// Access issues using a free-form JQL search.
     ///
     /// An example of a query: `project="CentOS Stream" AND priority = High`.
+    ///
+    /// With `Pagination::ChunkSize`, the search returns every matching issue at least once,
+    /// and never the same key twice, even if the issues change during the download.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue model.
     pub async fn search(&self, query: &str) -> Result<Vec<Issue>, JiraQueryError> {
+        self.search_with_options(query, &SearchOptions::default())
+            .await
+    }
+
+    /// Access issues using a free-form JQL search, with additional search options.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue model.
+    pub async fn search_with_options(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+    ) -> Result<Vec<Issue>, JiraQueryError> {
         let method = Method::Search(query);
 
         // If Pagination is set to ChunkSize, split the issue keys into chunk by chunk size
         // and request each chunk separately.
         if let Pagination::ChunkSize(chunk_size) = self.pagination {
-            self.paginated_issues(&method, chunk_size).await
+            self.paginated_issues(&method, options, chunk_size).await
         // If Pagination is not set to ChunkSize, use a single chunk request for all issues.
         } else {
-            let issues = self.chunk_of_issues(&method, 0).await?;
+            let issues = self.chunk_of_issues(&method, options, 0).await?.issues;
 
             Ok(issues)
         }
     }
+
+    /// Access a single page of the issues that match a free-form JQL search,
+    /// starting at the `start_at` position, together with the metadata of the results,
+    /// such as the `total` count, and the field names and types if the options expand them.
+    /// The page size is the one of the instance pagination, if it's set.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the issue model.
+    pub async fn search_page(
+        &self,
+        query: &str,
+        options: &SearchOptions,
+        start_at: u32,
+    ) -> Result<JqlResults, JiraQueryError> {
+        self.chunk_of_issues(&Method::Search(query), options, start_at)
+            .await
+    }
+
+    /// Stream all issues that match a free-form JQL search, page by page.
+    ///
+    /// The stream requests the next page only after it has yielded every issue
+    /// from the previous one. Each page starts where the previous one ended,
+    /// with the page size of the instance pagination, if it's set.
+    /// The stream ends once it reaches the `total` count of the search,
+    /// or when a page contains fewer issues than the page size.
+    ///
+    /// Like `JiraInstance::search`, the stream yields each key only once, and if the `total`
+    /// drops between pages, it steps back so that it doesn't skip the issues that moved
+    /// to lower positions.
+    ///
+    /// A failed request appears as an error item, after which the stream ends.
+    /// Collect the complete results with `TryStreamExt::try_collect`.
+    pub fn search_all<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Stream<Item = Result<Issue, JiraQueryError>> + 'a {
+        let pages = SearchPages {
+            instance: self,
+            query,
+            start_at: 0,
+            tracker: PageTracker::default(),
+            page: VecDeque::new(),
+            finished: false,
+        };
+
+        stream::unfold(pages, |mut pages| async move {
+            loop {
+                if let Some(issue) = pages.page.pop_front() {
+                    return Some((Ok(issue), pages));
+                }
+                if pages.finished {
+                    return None;
+                }
+                if let Err(error) = pages.next_page().await {
+                    pages.finished = true;
+                    return Some((Err(error), pages));
+                }
+            }
+        })
+    }
+}
+
+/// The progress of a search that streams its results.
+struct SearchPages<'a> {
+    instance: &'a JiraInstance,
+    query: &'a str,
+    start_at: u32,
+    tracker: PageTracker,
+    page: VecDeque<Issue>,
+    finished: bool,
+}
+
+/// The state that keeps a paginated search consistent while its results change:
+/// the keys that it has returned so far, and the total of the previous page.
+#[derive(Default)]
+struct PageTracker {
+    seen_keys: HashSet<String>,
+    previous_total: Option<u64>,
+}
+
+impl PageTracker {
+    /// Check that the issue hasn't appeared on an earlier page.
+    fn is_new(&mut self, issue: &Issue) -> bool {
+        let new = self.seen_keys.insert(issue.key.clone());
+        if !new {
+            log::debug!("Skipping a duplicate issue on a later page: {}", issue.key);
+        }
+        new
+    }
+
+    /// Record the total of the latest page, and return the number of positions
+    /// that the search must step back before the start of that page.
+    ///
+    /// If the total dropped since the previous page, some issues disappeared
+    /// from the result set and the remaining ones moved to lower positions.
+    /// Stepping back by the difference ensures that none of them is skipped.
+    fn step_back(&mut self, total: Option<u64>) -> u32 {
+        if let (Some(previous), Some(current)) = (self.previous_total, total) {
+            if current < previous {
+                log::debug!("The total dropped from {previous} to {current}. Stepping back.");
+                self.previous_total = total;
+                return u32::try_from(previous - current).unwrap_or(u32::MAX);
+            }
+        }
+        self.previous_total = total.or(self.previous_total);
+        0
+    }
+}
+
+impl SearchPages<'_> {
+    /// Download the page that follows the previous one,
+    /// and check whether it's the last page of the search.
+    async fn next_page(&mut self) -> Result<(), JiraQueryError> {
+        let method = Method::Search(self.query);
+        let results = self
+            .instance
+            .chunk_of_issues(&method, &SearchOptions::default(), self.start_at)
+            .await?;
+        // The page size that Jira applied, which might be lower than requested.
+        let max_results = u64::try_from(results.max_results).ok();
+        let total = u64::try_from(results.total).ok();
+        let page_len = u64::try_from(results.issues.len()).unwrap_or(u64::MAX);
+
+        for issue in results.issues {
+            if self.tracker.is_new(&issue) {
+                self.page.push_back(issue);
+            }
+        }
+
+        // Repeat the request from a lower position, rather than continue after this page.
+        let shift = self.tracker.step_back(total);
+        if shift > 0 {
+            self.start_at = self.start_at.saturating_sub(shift);
+            return Ok(());
+        }
+
+        self.start_at = self
+            .start_at
+            .saturating_add(u32::try_from(page_len).unwrap_or(u32::MAX));
+        self.finished = page_len == 0
+            || max_results.map_or(false, |max_results| page_len < max_results)
+            || total.map_or(false, |total| u64::from(self.start_at) >= total);
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn it_works() {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
+
+    /// Follow the `Retry-After` header, or back off exponentially, up to the maximum delay.
+    #[test]
+    fn retry_delays() {
+        let response = |retry_after: Option<&str>| {
+            let mut builder = http::Response::builder().status(429);
+            if let Some(retry_after) = retry_after {
+                builder = builder.header("Retry-After", retry_after);
+            }
+            reqwest::Response::from(builder.body(Vec::new()).unwrap())
+        };
+
+        assert_eq!(retry_delay(&response(Some("2")), 0), Duration::from_secs(2));
+        assert_eq!(retry_delay(&response(Some("86400")), 0), RETRY_MAX_DELAY);
+        assert_eq!(retry_delay(&response(None), 0), RETRY_BASE_DELAY);
+        assert_eq!(retry_delay(&response(None), 2), RETRY_BASE_DELAY * 4);
+        assert_eq!(retry_delay(&response(None), 20), RETRY_MAX_DELAY);
+    }
+
+    /// Clamp the interval of tiny rates instead of overflowing.
+    #[test]
+    fn rate_limit_intervals() {
+        assert_eq!(RateLimiter::new(4.0).interval, Duration::from_millis(250));
+        assert_eq!(RateLimiter::new(1e-20).interval, RATE_LIMIT_MAX_INTERVAL);
+        assert_eq!(
+            RateLimiter::new(f64::MIN_POSITIVE).interval,
+            RATE_LIMIT_MAX_INTERVAL
+        );
+        assert_eq!(RateLimiter::new(f64::MAX).interval, Duration::ZERO);
+    }
+
+    /// Compare the scheme, host, and port, but not the path.
+    #[test]
+    fn origins() {
+        let host = "https://jira.example.com";
+        assert!(same_origin(
+            "https://jira.example.com/images/icon.png",
+            host
+        ));
+        assert!(same_origin(
+            "https://jira.example.com:443/a",
+            "https://jira.example.com/jira"
+        ));
+        assert!(!same_origin(
+            "http://jira.example.com/images/icon.png",
+            host
+        ));
+        assert!(!same_origin(
+            "https://jira.example.com.evil.net/icon.png",
+            host
+        ));
+        assert!(!same_origin("https://jira.example.com:8443/icon.png", host));
+        assert!(!same_origin("/images/icon.png", host));
+    }
+
+    /// A Jira instance at a placeholder host, for testing URLs.
+    fn instance() -> JiraInstance {
+        JiraInstance::at("https://jira.example.com".to_string()).unwrap()
+    }
+
+    /// The `fieldsByKeys` parameter only appears when enabled.
+    #[test]
+    fn search_fields_by_keys() {
+        let method = Method::Search("project=TEST");
+
+        let default = instance().path(&method, &SearchOptions::new(), 0);
+        assert!(!default.contains("fieldsByKeys"));
+
+        let by_keys = instance().path(&method, &SearchOptions::new().fields_by_keys(true), 0);
+        assert_eq!(
+            by_keys,
+            "https://jira.example.com/rest/api/2/search?jql=project=TEST&startAt=0&fieldsByKeys=true"
+        );
+    }
+
+    #[test]
+    fn search_validation_level() {
+        let method = Method::Search("issue in linkedIssuesOf(TEST-1)");
+
+        let default = instance().path(&method, &SearchOptions::new(), 0);
+        assert!(!default.contains("validateQuery"));
+
+        let warn = SearchOptions::new().validate_query(ValidationLevel::Warn);
+        assert!(instance()
+            .path(&method, &warn, 0)
+            .ends_with("&startAt=0&validateQuery=warn"));
+        let none = SearchOptions::new().validate_query(ValidationLevel::None);
+        assert!(instance()
+            .path(&method, &none, 0)
+            .ends_with("&validateQuery=none"));
+    }
+
+    #[test]
+    fn search_default_fields() {
+        let method = Method::Search("project=TEST");
+        let narrow = instance().default_fields(&["summary", "status"]);
+
+        assert!(!instance()
+            .path(&method, &SearchOptions::new(), 0)
+            .contains("fields="));
+        assert!(narrow
+            .path(&method, &SearchOptions::new(), 0)
+            .ends_with("&startAt=0&fields=summary,status"));
+        // The fields of the search override the default.
+        let options = SearchOptions::new().fields(&["labels"]);
+        assert!(narrow
+            .path(&method, &options, 0)
+            .ends_with("&startAt=0&fields=labels"));
+    }
+
+    #[test]
+    fn search_with_names_and_schema() {
+        let method = Method::Search("project=TEST");
+
+        let options = SearchOptions::new().names(true).schema(true);
+        assert!(instance()
+            .path(&method, &options, 0)
+            .ends_with("&startAt=0&expand=names,schema"));
+        let options = SearchOptions::new().changelog(true).schema(true);
+        assert!(instance()
+            .path(&method, &options, 0)
+            .ends_with("&expand=changelog,schema"));
+    }
+
+    #[test]
+    fn search_with_changelog() {
+        let method = Method::Search("project=TEST");
+
+        let default = instance().path(&method, &SearchOptions::new(), 0);
+        assert!(!default.contains("expand"));
+
+        let changelog = SearchOptions::new().changelog(true);
+        assert!(instance()
+            .path(&method, &changelog, 0)
+            .ends_with("&startAt=0&expand=changelog"));
+    }
     // #[test]
     // fn issues() {
     //     let results = crate::issues("todo", &["todo"], "todo");
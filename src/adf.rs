@@ -0,0 +1,516 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Construct rich text in the Atlassian Document Format (ADF),
+//! which Jira Cloud uses for comments and descriptions in the version 3 REST API.
+//! Send the documents with `JiraInstance::add_comment_adf` and `NewIssue::description_adf`.
+//!
+//! See <https://developer.atlassian.com/cloud/jira/platform/apis/document/structure/>.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// A builder of an ADF document, which adds block nodes one after another.
+///
+/// ```
+/// use jira_query::{AdfBuilder, AdfInline};
+///
+/// let document = AdfBuilder::new()
+///     .paragraph(vec![
+///         AdfInline::text("The build is "),
+///         AdfInline::text("broken").bold(),
+///     ])
+///     .build();
+/// assert_eq!(document["type"], "doc");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdfBuilder {
+    content: Vec<Value>,
+}
+
+impl AdfBuilder {
+    /// Start an empty document.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a paragraph of inline content.
+    #[must_use]
+    pub fn paragraph(mut self, inlines: Vec<AdfInline>) -> Self {
+        self.content.push(paragraph(inlines));
+        self
+    }
+
+    /// Add a block of code, optionally with the language for syntax highlighting.
+    #[must_use]
+    pub fn code_block(mut self, language: Option<&str>, code: &str) -> Self {
+        let mut node = json!({
+            "type": "codeBlock",
+            "content": [{ "type": "text", "text": code }],
+        });
+        if let Some(language) = language {
+            node["attrs"] = json!({ "language": language });
+        }
+        self.content.push(node);
+        self
+    }
+
+    /// Add a bullet list, in which each item is a paragraph of inline content.
+    #[must_use]
+    pub fn bullet_list(mut self, items: Vec<Vec<AdfInline>>) -> Self {
+        let items: Vec<Value> = items
+            .into_iter()
+            .map(|inlines| json!({ "type": "listItem", "content": [paragraph(inlines)] }))
+            .collect();
+        self.content
+            .push(json!({ "type": "bulletList", "content": items }));
+        self
+    }
+
+    /// The JSON of the complete document.
+    #[must_use]
+    pub fn build(&self) -> Value {
+        json!({
+            "version": 1,
+            "type": "doc",
+            "content": self.content,
+        })
+    }
+}
+
+/// A paragraph node with the inline content.
+fn paragraph(inlines: Vec<AdfInline>) -> Value {
+    let content: Vec<Value> = inlines.into_iter().map(|inline| inline.node).collect();
+    json!({ "type": "paragraph", "content": content })
+}
+
+/// An inline node of an ADF document: a span of text or a mention of a user.
+///
+/// Text can carry marks, such as `AdfInline::text("Note").bold().italic()`.
+/// Marks don't apply to mentions, so the mark methods leave a mention unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdfInline {
+    node: Value,
+}
+
+impl AdfInline {
+    /// A span of plain text.
+    #[must_use]
+    pub fn text(text: &str) -> Self {
+        Self {
+            node: json!({ "type": "text", "text": text }),
+        }
+    }
+
+    /// A mention of the user with this account ID. The text is displayed
+    /// if the reader can't see the user, and is typically `@` and the display name.
+    #[must_use]
+    pub fn mention(account_id: &str, text: &str) -> Self {
+        Self {
+            node: json!({
+                "type": "mention",
+                "attrs": { "id": account_id, "text": text },
+            }),
+        }
+    }
+
+    /// Display the text in bold.
+    #[must_use]
+    pub fn bold(self) -> Self {
+        self.mark(json!({ "type": "strong" }))
+    }
+
+    /// Display the text in italics.
+    #[must_use]
+    pub fn italic(self) -> Self {
+        self.mark(json!({ "type": "em" }))
+    }
+
+    /// Display the text as inline code.
+    #[must_use]
+    pub fn code(self) -> Self {
+        self.mark(json!({ "type": "code" }))
+    }
+
+    /// Turn the text into a link to this URL.
+    #[must_use]
+    pub fn link(self, href: &str) -> Self {
+        self.mark(json!({ "type": "link", "attrs": { "href": href } }))
+    }
+
+    /// Add a mark to a text node.
+    fn mark(mut self, mark: Value) -> Self {
+        if self.node["type"] == "text" {
+            match self.node.get_mut("marks").and_then(Value::as_array_mut) {
+                Some(marks) => marks.push(mark),
+                None => self.node["marks"] = json!([mark]),
+            }
+        }
+        self
+    }
+}
+
+/// A parsed ADF document, such as a comment body from Jira Cloud.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdfDocument {
+    pub version: u32,
+    #[serde(default)]
+    pub content: Vec<AdfNode>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A node of an ADF document: either a block, such as a paragraph,
+/// or an inline node, such as a span of text with its marks.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdfNode {
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content: Vec<AdfNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub marks: Vec<AdfMark>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub attrs: Value,
+}
+
+/// A mark on a text node, such as `strong` or `link`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AdfMark {
+    #[serde(rename = "type")]
+    pub mark_type: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub attrs: Value,
+}
+
+/// A rich text field, such as a comment body or an issue description.
+///
+/// Jira Server sends rich text as a string in the wiki markup,
+/// while Jira Cloud sends it as an ADF document.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Body {
+    Text(String),
+    Adf(AdfDocument),
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl Body {
+    /// The plain text of the field: the string as is, or the text of the ADF document
+    /// with one line per paragraph or other block.
+    #[must_use]
+    pub fn to_plain_text(&self) -> String {
+        match self {
+            Self::Text(text) => text.clone(),
+            Self::Adf(document) => document.to_plain_text(),
+        }
+    }
+}
+
+impl AdfDocument {
+    /// The text of the document, with one line per paragraph or other block.
+    #[must_use]
+    pub fn to_plain_text(&self) -> String {
+        join_blocks(&self.content)
+    }
+
+    /// Render the document as HTML, such as for a web UI.
+    ///
+    /// The HTML covers paragraphs, headings, lists, quotes, code blocks, links,
+    /// and the inline marks. All text from the document is escaped, and links
+    /// only keep the `http`, `https`, and `mailto` schemes, so the HTML is safe to embed.
+    /// Unknown nodes render their content without any markup.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        self.content.iter().map(AdfNode::to_html).collect()
+    }
+}
+
+impl AdfNode {
+    /// Render the node and its content as HTML. See `AdfDocument::to_html`.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let inner: String = self.content.iter().map(Self::to_html).collect();
+
+        match self.node_type.as_str() {
+            "text" => self.text_html(),
+            "paragraph" => format!("<p>{inner}</p>"),
+            "heading" => {
+                let level = self.attrs["level"].as_u64().unwrap_or(1).clamp(1, 6);
+                format!("<h{level}>{inner}</h{level}>")
+            }
+            "bulletList" => format!("<ul>{inner}</ul>"),
+            "orderedList" => format!("<ol>{inner}</ol>"),
+            "listItem" => format!("<li>{inner}</li>"),
+            "blockquote" => format!("<blockquote>{inner}</blockquote>"),
+            "codeBlock" => {
+                let code: String = self
+                    .content
+                    .iter()
+                    .filter_map(|node| node.text.as_deref())
+                    .collect();
+                match self.attrs["language"].as_str() {
+                    Some(language) => format!(
+                        "<pre><code class=\"language-{}\">{}</code></pre>",
+                        escape_html(language),
+                        escape_html(&code)
+                    ),
+                    None => format!("<pre><code>{}</code></pre>", escape_html(&code)),
+                }
+            }
+            "rule" => "<hr>".to_string(),
+            "hardBreak" => "<br>".to_string(),
+            "mention" | "emoji" => {
+                let text = self.attrs["text"]
+                    .as_str()
+                    .or_else(|| self.attrs["shortName"].as_str())
+                    .unwrap_or_default();
+                escape_html(text)
+            }
+            _ => inner,
+        }
+    }
+
+    /// The text of the node and its content. See `AdfDocument::to_plain_text`.
+    #[must_use]
+    pub fn to_plain_text(&self) -> String {
+        match self.node_type.as_str() {
+            "text" => self.text.clone().unwrap_or_default(),
+            "hardBreak" => "\n".to_string(),
+            "mention" | "emoji" => self.attrs["text"]
+                .as_str()
+                .or_else(|| self.attrs["shortName"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            _ if self.content.iter().any(AdfNode::is_inline) => {
+                self.content.iter().map(Self::to_plain_text).collect()
+            }
+            _ => join_blocks(&self.content),
+        }
+    }
+
+    /// Whether the node belongs inside a block, rather than being a block itself.
+    fn is_inline(&self) -> bool {
+        matches!(
+            self.node_type.as_str(),
+            "text" | "hardBreak" | "mention" | "emoji" | "inlineCard" | "date" | "status"
+        )
+    }
+
+    /// Render a text node, wrapped in the HTML elements of its marks.
+    fn text_html(&self) -> String {
+        let mut html = escape_html(self.text.as_deref().unwrap_or_default());
+
+        for mark in &self.marks {
+            html = match mark.mark_type.as_str() {
+                "strong" => format!("<strong>{html}</strong>"),
+                "em" => format!("<em>{html}</em>"),
+                "code" => format!("<code>{html}</code>"),
+                "strike" => format!("<s>{html}</s>"),
+                "underline" => format!("<u>{html}</u>"),
+                "link" => match mark.attrs["href"]
+                    .as_str()
+                    .filter(|href| is_safe_link(href))
+                {
+                    Some(href) => format!("<a href=\"{}\">{html}</a>", escape_html(href)),
+                    None => html,
+                },
+                _ => html,
+            };
+        }
+
+        html
+    }
+}
+
+/// The text of the block nodes, one per line.
+fn join_blocks(blocks: &[AdfNode]) -> String {
+    blocks
+        .iter()
+        .map(AdfNode::to_plain_text)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escape the characters that have a special meaning in HTML text and attributes.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Whether the link target uses a scheme that can't run scripts, or no scheme at all.
+fn is_safe_link(href: &str) -> bool {
+    let href = href.trim().to_lowercase();
+    match href.find(':') {
+        Some(colon) => {
+            let scheme = &href[..colon];
+            // A colon after a slash, a question mark, or a hash isn't part of a scheme.
+            scheme.contains(['/', '?', '#']) || ["http", "https", "mailto"].contains(&scheme)
+        }
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render a document with a heading, a link, a code block, and text that needs escaping.
+    #[test]
+    fn render_html() {
+        let document: AdfDocument = serde_json::from_value(json!({
+            "version": 1,
+            "type": "doc",
+            "content": [
+                {
+                    "type": "heading",
+                    "attrs": { "level": 2 },
+                    "content": [{ "type": "text", "text": "Steps" }],
+                },
+                {
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "See " },
+                        {
+                            "type": "text",
+                            "text": "the docs",
+                            "marks": [
+                                { "type": "link", "attrs": { "href": "https://example.com/?a=1&b=2" } },
+                                { "type": "strong" },
+                            ],
+                        },
+                        { "type": "text", "text": " & <script>alert(1)</script>" },
+                    ],
+                },
+                {
+                    "type": "codeBlock",
+                    "attrs": { "language": "rust" },
+                    "content": [{ "type": "text", "text": "if a < b {}" }],
+                },
+                {
+                    "type": "bulletList",
+                    "content": [{
+                        "type": "listItem",
+                        "content": [{
+                            "type": "paragraph",
+                            "content": [{
+                                "type": "text",
+                                "text": "click",
+                                "marks": [{ "type": "link", "attrs": { "href": "javascript:alert(1)" } }],
+                            }],
+                        }],
+                    }],
+                },
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            document.to_html(),
+            "<h2>Steps</h2>\
+             <p>See <strong><a href=\"https://example.com/?a=1&amp;b=2\">the docs</a></strong> \
+             &amp; &lt;script&gt;alert(1)&lt;/script&gt;</p>\
+             <pre><code class=\"language-rust\">if a &lt; b {}</code></pre>\
+             <ul><li><p>click</p></li></ul>"
+        );
+    }
+
+    /// Build a paragraph with a bold span and a mention.
+    #[test]
+    fn paragraph_with_bold_and_mention() {
+        let document = AdfBuilder::new()
+            .paragraph(vec![
+                AdfInline::text("Please review, "),
+                AdfInline::mention("5b10a2844c20165700ede21g", "@Jane Doe"),
+                AdfInline::text(". It's "),
+                AdfInline::text("urgent").bold().italic(),
+                AdfInline::mention("5b10a2844c20165700ede21g", "@Jane Doe").bold(),
+            ])
+            .build();
+
+        assert_eq!(
+            document,
+            json!({
+                "version": 1,
+                "type": "doc",
+                "content": [{
+                    "type": "paragraph",
+                    "content": [
+                        { "type": "text", "text": "Please review, " },
+                        {
+                            "type": "mention",
+                            "attrs": { "id": "5b10a2844c20165700ede21g", "text": "@Jane Doe" },
+                        },
+                        { "type": "text", "text": ". It's " },
+                        {
+                            "type": "text",
+                            "text": "urgent",
+                            "marks": [{ "type": "strong" }, { "type": "em" }],
+                        },
+                        {
+                            "type": "mention",
+                            "attrs": { "id": "5b10a2844c20165700ede21g", "text": "@Jane Doe" },
+                        },
+                    ],
+                }],
+            })
+        );
+    }
+
+    /// Build a link, a code block, and a bullet list.
+    #[test]
+    fn blocks_and_links() {
+        let document = AdfBuilder::new()
+            .paragraph(vec![AdfInline::text("docs").link("https://example.com")])
+            .code_block(Some("rust"), "fn main() {}")
+            .bullet_list(vec![
+                vec![AdfInline::text("one")],
+                vec![AdfInline::text("two").code()],
+            ])
+            .build();
+        let content = &document["content"];
+
+        assert_eq!(
+            content[0]["content"][0]["marks"],
+            json!([{ "type": "link", "attrs": { "href": "https://example.com" } }])
+        );
+        assert_eq!(
+            content[1],
+            json!({
+                "type": "codeBlock",
+                "attrs": { "language": "rust" },
+                "content": [{ "type": "text", "text": "fn main() {}" }],
+            })
+        );
+        assert_eq!(content[2]["type"], "bulletList");
+        assert_eq!(content[2]["content"][1]["type"], "listItem");
+        assert_eq!(
+            content[2]["content"][1]["content"][0]["content"][0]["marks"],
+            json!([{ "type": "code" }])
+        );
+    }
+}
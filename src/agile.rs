@@ -0,0 +1,320 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The sprints of agile boards, which use the Jira Software REST API
+//! rather than the platform REST API.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::access::JiraInstance;
+use crate::errors::JiraQueryError;
+use crate::issue_model::{Issue, JqlResults};
+
+// The prefix of the Jira Software REST requests.
+const AGILE_PREFIX: &str = "rest/agile/1.0";
+
+/// A sprint of an agile board.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Sprint {
+    pub id: i64,
+    pub name: String,
+    /// The state of the sprint: `future`, `active`, or `closed`.
+    pub state: String,
+    #[serde(rename = "startDate")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(rename = "endDate")]
+    pub end_date: Option<DateTime<Utc>>,
+    #[serde(rename = "completeDate")]
+    pub complete_date: Option<DateTime<Utc>>,
+    #[serde(rename = "originBoardId")]
+    pub origin_board_id: Option<i64>,
+    pub goal: Option<String>,
+    /// The REST URL of the sprint. The sprint custom field of an issue omits it.
+    #[serde(rename = "self", skip_serializing_if = "Option::is_none")]
+    pub self_link: Option<String>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The IDs of the custom fields that Jira Software uses for agile planning.
+/// The IDs differ between instances. Find them with `JiraInstance::field_metadata`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AgileFields {
+    pub sprint: String,
+    pub story_points: String,
+}
+
+impl Default for AgileFields {
+    /// The usual IDs on Jira Cloud.
+    fn default() -> Self {
+        Self {
+            sprint: "customfield_10020".to_string(),
+            story_points: "customfield_10016".to_string(),
+        }
+    }
+}
+
+/// A sprint in the sprint custom field: an object on Jira Cloud,
+/// or a string with the attributes on older versions of Jira Server.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SprintValue {
+    Object(Sprint),
+    Text(String),
+}
+
+impl Sprint {
+    /// Parse the sprints from the value of the sprint custom field.
+    pub(crate) fn from_field(value: &Value) -> Result<Vec<Self>, serde_json::Error> {
+        let values = Vec::<SprintValue>::deserialize(value)?;
+
+        values
+            .into_iter()
+            .map(|value| match value {
+                SprintValue::Object(sprint) => Ok(sprint),
+                SprintValue::Text(text) => Self::from_text(&text).ok_or_else(|| {
+                    serde::de::Error::custom(format!("Invalid sprint in the sprint field: {text}"))
+                }),
+            })
+            .collect()
+    }
+
+    /// Parse the string form of a sprint, such as
+    /// `com.atlassian.greenhopper.service.sprint.Sprint@1f7e[id=37,state=ACTIVE,name=Sprint 12,...]`.
+    /// The goal or the name might contain commas, so a comma only starts a new attribute
+    /// if a known attribute name and `=` follow it.
+    fn from_text(text: &str) -> Option<Self> {
+        const KEYS: [&str; 11] = [
+            "id",
+            "rapidViewId",
+            "state",
+            "name",
+            "startDate",
+            "endDate",
+            "completeDate",
+            "activatedDate",
+            "sequence",
+            "goal",
+            "autoStartStop",
+        ];
+
+        let start = text.find('[')? + 1;
+        let end = text.rfind(']')?;
+        let mut attributes: Vec<(&str, String)> = Vec::new();
+
+        for part in text.get(start..end)?.split(',') {
+            let new_attribute = part.split_once('=').filter(|(key, _)| KEYS.contains(key));
+            match (new_attribute, attributes.last_mut()) {
+                (Some((key, value)), _) => attributes.push((key, value.to_string())),
+                (None, Some((_, value))) => {
+                    value.push(',');
+                    value.push_str(part);
+                }
+                (None, None) => return None,
+            }
+        }
+
+        let attribute = |name: &str| {
+            attributes
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.as_str())
+                .filter(|value| *value != "<null>")
+        };
+        let date = |name: &str| attribute(name).and_then(|value| value.parse().ok());
+
+        Some(Self {
+            id: attribute("id")?.parse().ok()?,
+            name: attribute("name").unwrap_or_default().to_string(),
+            state: attribute("state").unwrap_or_default().to_lowercase(),
+            start_date: date("startDate"),
+            end_date: date("endDate"),
+            complete_date: date("completeDate"),
+            origin_board_id: attribute("rapidViewId").and_then(|value| value.parse().ok()),
+            goal: attribute("goal").map(ToString::to_string),
+            self_link: None,
+            extra: Value::Null,
+        })
+    }
+}
+
+/// A page of sprints in the response from Jira.
+#[derive(Clone, Debug, Deserialize)]
+struct SprintPage {
+    values: Vec<Sprint>,
+    #[serde(rename = "isLast", default)]
+    is_last: bool,
+}
+
+impl JiraInstance {
+    /// Read the sprints of the issues from the custom field with this ID,
+    /// rather than the default `customfield_10020`.
+    #[must_use]
+    pub fn with_sprint_field(mut self, field_id: &str) -> Self {
+        self.agile_fields.sprint = field_id.to_string();
+        self
+    }
+
+    /// Read the story points of the issues from the custom field with this ID,
+    /// rather than the default `customfield_10016`.
+    #[must_use]
+    pub fn with_story_points_field(mut self, field_id: &str) -> Self {
+        self.agile_fields.story_points = field_id.to_string();
+        self
+    }
+
+    /// The sprints of the issue, from the sprint custom field of the instance.
+    /// See `Fields::sprints`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value of the field isn't a list of sprints.
+    pub fn sprints_of(&self, issue: &Issue) -> Result<Vec<Sprint>, serde_json::Error> {
+        issue.fields.sprints(&self.agile_fields.sprint)
+    }
+
+    /// The story points of the issue, from the story points custom field of the instance.
+    /// See `Fields::story_points`.
+    #[must_use]
+    pub fn story_points_of(&self, issue: &Issue) -> Option<f64> {
+        issue.fields.story_points(&self.agile_fields.story_points)
+    }
+
+    /// The full URL of a Jira Software REST request, such as `board/12/sprint`.
+    fn agile_url(&self, fragment: &str) -> String {
+        format!("{}/{}/{}", self.host, AGILE_PREFIX, fragment)
+    }
+
+    /// List the active and future sprints of the board with this ID,
+    /// in the order that the board shows them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the sprint model.
+    pub async fn open_sprints(&self, board_id: i64) -> Result<Vec<Sprint>, JiraQueryError> {
+        let mut sprints = Vec::new();
+
+        loop {
+            let url = self.agile_url(&format!(
+                "board/{board_id}/sprint?state=active,future&startAt={}",
+                sprints.len()
+            ));
+            let page = self.get_json::<SprintPage>(&url).await?;
+
+            let last = page.is_last || page.values.is_empty();
+            sprints.extend(page.values);
+            if last {
+                break;
+            }
+        }
+
+        log::debug!("{sprints:#?}");
+
+        Ok(sprints)
+    }
+
+    /// Download the issues in each active and future sprint of the board with this ID,
+    /// keyed by the sprint ID. A sprint without issues maps to an empty list.
+    ///
+    /// # Errors
+    ///
+    /// Fails if any request to Jira fails or if the response doesn't match the model.
+    pub async fn issues_by_sprint(
+        &self,
+        board_id: i64,
+    ) -> Result<HashMap<i64, Vec<Issue>>, JiraQueryError> {
+        let mut by_sprint = HashMap::new();
+
+        for sprint in self.open_sprints(board_id).await? {
+            let issues = self.sprint_issues(board_id, sprint.id).await?;
+            by_sprint.insert(sprint.id, issues);
+        }
+
+        Ok(by_sprint)
+    }
+
+    /// Download all issues in the sprint, page by page.
+    async fn sprint_issues(
+        &self,
+        board_id: i64,
+        sprint_id: i64,
+    ) -> Result<Vec<Issue>, JiraQueryError> {
+        let mut issues = Vec::new();
+
+        loop {
+            let url = self.agile_url(&format!(
+                "board/{board_id}/sprint/{sprint_id}/issue?startAt={}",
+                issues.len()
+            ));
+            let page = self.get_json::<JqlResults>(&url).await?;
+
+            let empty = page.issues.is_empty();
+            issues.extend(page.issues);
+            if empty || issues.len() >= usize::try_from(page.total).unwrap_or_default() {
+                break;
+            }
+        }
+
+        Ok(issues)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Parse the sprint field in both the object form and the string form of Jira Server.
+    #[test]
+    fn sprint_field_forms() {
+        let field = serde_json::json!([
+            {
+                "id": 37,
+                "name": "Sprint 12",
+                "state": "closed",
+                "boardId": 5,
+                "goal": "",
+                "startDate": "2023-01-09T09:00:00.000Z",
+                "endDate": "2023-01-23T09:00:00.000Z",
+                "completeDate": "2023-01-23T10:00:00.000Z"
+            },
+            "com.atlassian.greenhopper.service.sprint.Sprint@1f7e2d[id=38,rapidViewId=5,state=ACTIVE,name=Sprint 13,startDate=2023-01-23T09:00:00.000Z,endDate=2023-02-06T09:00:00.000Z,completeDate=<null>,activatedDate=2023-01-23T09:00:00.000Z,sequence=38,goal=Fix the crash, then ship,autoStartStop=false]"
+        ]);
+
+        let sprints = Sprint::from_field(&field).unwrap();
+
+        assert_eq!(sprints[0].id, 37);
+        assert_eq!(sprints[0].state, "closed");
+        assert_eq!(sprints[0].self_link, None);
+        assert_eq!(sprints[1].id, 38);
+        assert_eq!(sprints[1].name, "Sprint 13");
+        assert_eq!(sprints[1].state, "active");
+        assert_eq!(sprints[1].origin_board_id, Some(5));
+        assert_eq!(sprints[1].goal.as_deref(), Some("Fix the crash, then ship"));
+        assert_eq!(
+            sprints[1].start_date,
+            Some(Utc.with_ymd_and_hms(2023, 1, 23, 9, 0, 0).unwrap())
+        );
+        assert_eq!(sprints[1].complete_date, None);
+
+        assert!(Sprint::from_field(&serde_json::json!(["Sprint without attributes"])).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Record the HTTP exchanges with Jira in a directory, and replay them later
+//! without a network connection, such as for deterministic tests and demos.
+//!
+//! Each exchange is a JSON file, named after the signature of the request:
+//! the method, the path and query of the URL, and the request body.
+//! The host isn't part of the signature, so a recording from one instance
+//! replays against any host.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::JiraQueryError;
+
+/// Send the requests to Jira as usual, and also write each request and its response
+/// to a file in the directory. Set it with `JiraInstance::record`.
+#[derive(Clone, Debug)]
+pub struct RecordingTransport {
+    dir: PathBuf,
+}
+
+impl RecordingTransport {
+    /// Record the exchanges in this directory, which is created if it doesn't exist.
+    /// A new recording of the same request replaces the previous one.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+/// Answer the requests with the responses from a directory that `RecordingTransport`
+/// has written, without sending anything to Jira. Set it with `JiraInstance::replay`.
+#[derive(Clone, Debug)]
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    /// Replay the exchanges from this directory.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+/// The way that the instance exchanges requests and responses.
+#[derive(Clone, Debug)]
+pub(crate) enum Transport {
+    Network,
+    Record(RecordingTransport),
+    Replay(ReplayTransport),
+}
+
+impl Default for Transport {
+    fn default() -> Self {
+        Self::Network
+    }
+}
+
+/// A recorded request and its response.
+#[derive(Debug, Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    url: String,
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: RecordedBody,
+}
+
+/// The body of a recorded response: text if it's valid UTF-8, such as JSON,
+/// and otherwise the bytes, such as an image.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordedBody {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Transport {
+    /// Send the request over the network, or replay its recorded response.
+    pub(crate) async fn execute(
+        &self,
+        client: &reqwest::Client,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, JiraQueryError> {
+        match self {
+            Self::Network => Ok(client.execute(request).await?),
+            Self::Record(recording) => {
+                let method = request.method().to_string();
+                let url = signature_url(&request);
+                let path = recording.dir.join(file_name(&request));
+
+                let response = client.execute(request).await?;
+                let exchange = Exchange {
+                    method,
+                    url,
+                    status: response.status().as_u16(),
+                    headers: response
+                        .headers()
+                        .iter()
+                        .filter_map(|(name, value)| {
+                            Some((name.to_string(), value.to_str().ok()?.to_string()))
+                        })
+                        .collect(),
+                    body: match String::from_utf8(response.bytes().await?.to_vec()) {
+                        Ok(text) => RecordedBody::Text(text),
+                        Err(error) => RecordedBody::Bytes(error.into_bytes()),
+                    },
+                };
+
+                fs::create_dir_all(&recording.dir)?;
+                serde_json::to_writer_pretty(BufWriter::new(File::create(&path)?), &exchange)?;
+                log::debug!(
+                    "Recorded {} {} in {}",
+                    exchange.method,
+                    exchange.url,
+                    path.display()
+                );
+
+                exchange.into_response(path)
+            }
+            Self::Replay(replay) => {
+                let path = replay.dir.join(file_name(&request));
+                let file = File::open(&path).map_err(|_| JiraQueryError::MissingRecording {
+                    method: request.method().to_string(),
+                    url: signature_url(&request),
+                })?;
+                let exchange: Exchange = serde_json::from_reader(BufReader::new(file))?;
+
+                exchange.into_response(path)
+            }
+        }
+    }
+}
+
+impl Exchange {
+    /// Turn the recording back into a response, as if it came from Jira.
+    fn into_response(self, path: PathBuf) -> Result<reqwest::Response, JiraQueryError> {
+        let body = match self.body {
+            RecordedBody::Text(text) => text.into_bytes(),
+            RecordedBody::Bytes(bytes) => bytes,
+        };
+
+        let mut builder = http::Response::builder().status(self.status);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(body)
+            .map_err(|_| JiraQueryError::InvalidRecording(path))?;
+
+        Ok(reqwest::Response::from(response))
+    }
+}
+
+/// The part of the request URL that identifies the request regardless of the host.
+fn signature_url(request: &reqwest::Request) -> String {
+    let url = request.url();
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+/// The name of the file that records the request, which is a hash of its signature.
+///
+/// The hash uses the FNV-1a function rather than the standard hasher,
+/// whose results might change between Rust versions and invalidate the recordings.
+fn file_name(request: &reqwest::Request) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let url = signature_url(request);
+    let body = request
+        .body()
+        .and_then(reqwest::Body::as_bytes)
+        .unwrap_or_default();
+    let signature = [
+        request.method().as_str().as_bytes(),
+        b" ",
+        url.as_bytes(),
+        b"\n",
+        body,
+    ];
+
+    let hash = signature
+        .iter()
+        .flat_map(|part| part.iter())
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+        });
+
+    format!(
+        "{}-{hash:016x}.json",
+        request.method().as_str().to_lowercase()
+    )
+}
@@ -0,0 +1,140 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Flow metrics of an issue, computed from the status changes in its changelog.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::access::JiraInstance;
+use crate::errors::JiraQueryError;
+use crate::issue_model::Issue;
+
+// The status that starts the cycle time.
+const IN_PROGRESS: &str = "In Progress";
+
+/// The time that an issue took to resolve, and the time it spent in each status.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleTime {
+    /// The time from the creation to the resolution. `None` if the issue isn't resolved.
+    pub lead_time: Option<Duration>,
+    /// The time from the first move to the `In Progress` status to the resolution.
+    /// `None` if the issue isn't resolved or never was in progress.
+    pub cycle_time: Option<Duration>,
+    /// The total time in each status, keyed by the status name. A status that the issue
+    /// entered several times adds up all the visits.
+    pub status_durations: HashMap<String, Duration>,
+}
+
+/// A change of the status, from the changelog.
+struct StatusChange<'a> {
+    time: DateTime<Utc>,
+    from: Option<&'a str>,
+    to: Option<&'a str>,
+}
+
+impl Issue {
+    /// Compute the flow metrics of the issue from the status changes in `Issue::changelog`.
+    /// Without a changelog, the issue counts as if it had stayed in its current status.
+    ///
+    /// The status durations of a resolved issue run until the resolution.
+    /// For an unresolved issue, the current status runs until `now`.
+    #[must_use]
+    pub fn cycle_time(&self, now: DateTime<Utc>) -> CycleTime {
+        let fields = &self.fields;
+
+        let mut changes: Vec<StatusChange> = self
+            .changelog
+            .iter()
+            .flat_map(|changelog| &changelog.histories)
+            .flat_map(|history| {
+                history
+                    .items
+                    .iter()
+                    .filter(|item| item.field == "status")
+                    .map(move |item| StatusChange {
+                        time: history.created,
+                        from: item.from_string.as_deref(),
+                        to: item.to_string.as_deref(),
+                    })
+            })
+            .collect();
+        changes.sort_by_key(|change| change.time);
+
+        let end = fields.resolutiondate.unwrap_or(now);
+        let mut status_durations = HashMap::new();
+        let mut status = changes
+            .first()
+            .and_then(|change| change.from)
+            .unwrap_or(&fields.status.name);
+        let mut since = fields.created;
+
+        for change in &changes {
+            let time = change.time.min(end);
+            add_duration(&mut status_durations, status, time - since);
+            status = change.to.unwrap_or_default();
+            since = time;
+        }
+        add_duration(&mut status_durations, status, end - since);
+
+        let started = changes
+            .iter()
+            .find(|change| {
+                change
+                    .to
+                    .map_or(false, |to| to.eq_ignore_ascii_case(IN_PROGRESS))
+            })
+            .map(|change| change.time);
+
+        CycleTime {
+            lead_time: fields
+                .resolutiondate
+                .map(|resolved| resolved - fields.created),
+            cycle_time: fields
+                .resolutiondate
+                .zip(started)
+                .map(|(resolved, started)| resolved - started),
+            status_durations,
+        }
+    }
+}
+
+/// Add the time to the total of the status. Changes that Jira recorded after the resolution
+/// or out of order would make the time negative, so they count as zero.
+fn add_duration(durations: &mut HashMap<String, Duration>, status: &str, time: Duration) {
+    let total = durations
+        .entry(status.to_string())
+        .or_insert_with(Duration::zero);
+    *total += time.max(Duration::zero());
+}
+
+impl JiraInstance {
+    /// Download the issue with its complete changelog and compute its flow metrics:
+    /// the lead time, the cycle time, and the time in each status.
+    /// See `Issue::cycle_time`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::NotFound` or `JiraQueryError::ProjectNotFound`
+    /// if Jira finds no such issue or project, or if the request to Jira fails
+    /// or the response doesn't match the issue model.
+    pub async fn cycle_time(&self, key: &str) -> Result<CycleTime, JiraQueryError> {
+        let issue = self.issue_with_changelog(key).await?;
+
+        Ok(issue.cycle_time(Utc::now()))
+    }
+}
@@ -0,0 +1,165 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The development panel of an issue, which lists the branches, commits,
+//! and pull requests from the Git hosting services linked to Jira.
+//!
+//! The panel uses the internal `dev-status` REST API, which isn't part of the official
+//! Jira REST API and might change between Jira versions.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::access::JiraInstance;
+use crate::errors::JiraQueryError;
+
+// The prefix of the development panel REST requests.
+const DEV_STATUS_PREFIX: &str = "rest/dev-status/1.0";
+
+// The kinds of development information that the panel lists.
+const DATA_TYPES: [&str; 3] = ["repository", "branch", "pullrequest"];
+
+/// The branches, commits, and pull requests linked to an issue.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DevelopmentInfo {
+    pub branches: Vec<DevBranch>,
+    pub commits: Vec<DevCommit>,
+    pub pull_requests: Vec<DevPullRequest>,
+}
+
+/// A Git branch that mentions the issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DevBranch {
+    pub name: String,
+    pub url: String,
+    pub repository: DevRepository,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A Git repository in the development panel.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DevRepository {
+    pub name: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A Git commit that mentions the issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DevCommit {
+    pub id: String,
+    #[serde(rename = "displayId")]
+    pub display_id: String,
+    pub message: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A pull request that mentions the issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DevPullRequest {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A repository as listed in the detail response, including its commits.
+#[derive(Clone, Debug, Deserialize)]
+struct DevRepositoryCommits {
+    #[serde(default)]
+    commits: Vec<DevCommit>,
+}
+
+/// The development information from a single linked Git hosting service.
+#[derive(Clone, Debug, Deserialize)]
+struct DevDetail {
+    #[serde(default)]
+    branches: Vec<DevBranch>,
+    #[serde(default)]
+    repositories: Vec<DevRepositoryCommits>,
+    #[serde(default, rename = "pullRequests")]
+    pull_requests: Vec<DevPullRequest>,
+}
+
+/// The response to a detail request of the development panel.
+#[derive(Clone, Debug, Deserialize)]
+struct DevDetailResponse {
+    detail: Vec<DevDetail>,
+}
+
+impl JiraInstance {
+    /// Access the development panel of the issue with this numeric ID (not its key),
+    /// listing the linked branches, commits, and pull requests.
+    ///
+    /// The method first reads the panel summary to find out which Git hosting services
+    /// have any information about the issue, and then downloads the details from each.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the development model.
+    pub async fn development_info(
+        &self,
+        issue_id: &str,
+    ) -> Result<DevelopmentInfo, JiraQueryError> {
+        let summary_url = format!(
+            "{}/{}/issue/summary?issueId={}",
+            self.host, DEV_STATUS_PREFIX, issue_id
+        );
+        let summary = self.get_json::<Value>(&summary_url).await?;
+
+        // The application types that have any information, such as `github` or `stash`.
+        let application_types: BTreeSet<&str> = DATA_TYPES
+            .iter()
+            .filter_map(|data_type| summary["summary"][data_type]["byInstanceType"].as_object())
+            .flat_map(|instances| instances.keys().map(String::as_str))
+            .collect();
+
+        let mut info = DevelopmentInfo::default();
+
+        for application_type in application_types {
+            for data_type in DATA_TYPES {
+                let detail_url = format!(
+                    "{}/{}/issue/detail?issueId={}&applicationType={}&dataType={}",
+                    self.host, DEV_STATUS_PREFIX, issue_id, application_type, data_type
+                );
+                let response = self.get_json::<DevDetailResponse>(&detail_url).await?;
+
+                for detail in response.detail {
+                    info.branches.extend(detail.branches);
+                    info.pull_requests.extend(detail.pull_requests);
+                    info.commits.extend(
+                        detail
+                            .repositories
+                            .into_iter()
+                            .flat_map(|repository| repository.commits),
+                    );
+                }
+            }
+        }
+
+        log::debug!("{info:#?}");
+
+        Ok(info)
+    }
+}
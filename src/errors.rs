@@ -14,6 +14,11 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use thiserror::Error;
 
 /// All errors that might occur in this crate.
@@ -23,6 +28,160 @@ pub enum JiraQueryError {
     MissingIssues(Vec<String>),
     #[error("The Jira query returned no issues.")]
     NoIssues,
+    #[error("The issue {issue} has no transition that resolves it.")]
+    MissingTransition { issue: String },
+    /// Jira rejected the transition (HTTP 400), usually because the transition isn't
+    /// available from the current status of the issue. The message explains the rejection.
+    #[error("The issue {issue} can't go through the transition {transition}: {message}")]
+    InvalidTransition {
+        issue: String,
+        transition: String,
+        message: String,
+    },
+    #[error("The Jira instance has no resolution named {0}.")]
+    MissingResolution(String),
+    /// Jira found no such comment (HTTP 404). Either the comment doesn't exist,
+    /// or the issue doesn't exist or isn't visible to the user.
+    #[error("The issue {issue} has no comment with the ID {comment}.")]
+    MissingComment { issue: String, comment: String },
+    /// The visibility of a comment must have the `group` or `role` type.
+    #[error("The visibility type {0} is neither `group` nor `role`.")]
+    InvalidVisibility(String),
+    /// Somebody changed the issue after the time that the update expected,
+    /// so the update didn't happen. Read the issue again and reapply the change.
+    #[error("The issue {key} changed at {updated}, after it was last read.")]
+    Conflict { key: String, updated: DateTime<Utc> },
+    /// The issue key contains characters that no issue key can have,
+    /// such as spaces, quotes, or parentheses.
+    #[error("The text {0:?} isn't a valid issue key.")]
+    InvalidKey(String),
+    /// Jira found no issue with this key (HTTP 404). Jira gives the same answer
+    /// if the issue exists, but the user lacks the permission to browse it,
+    /// so that the response doesn't reveal the issue.
+    #[error("The issue {key} doesn't exist, or the user can't see it.")]
+    NotFound { key: String },
+    /// Jira found no project with this key (HTTP 404). As with issues, the project
+    /// might also exist, but the user lacks the permission to browse it.
+    #[error("The project {key} doesn't exist, or the user can't see it.")]
+    ProjectNotFound { key: String },
+    /// Jira rejected the credentials, which are missing, wrong, or expired (HTTP 401).
+    /// Authenticate again with valid credentials.
+    #[error("Jira rejected the credentials.")]
+    Unauthorized,
+    /// Jira accepted the credentials, but the user lacks the permission
+    /// to perform the request (HTTP 403). Request access from the Jira administrators.
+    #[error("The user lacks the permission to perform the request.")]
+    Forbidden,
+    /// Jira kept responding with a transient error status, such as 429 or 503,
+    /// even after all the retries that the instance allows.
+    #[error("Jira responded with the status {status} even after {retries} retries.")]
+    RetriesExhausted { retries: u32, status: u16 },
+    /// Jira responded with an error status that has no more specific variant,
+    /// such as 400 or 500. The body of the response usually explains the error,
+    /// and is shortened if it's long.
+    #[error("Jira responded with the status {status}: {body}")]
+    Http { status: u16, body: String },
+    /// The response from Jira doesn't match the model, such as when the instance
+    /// returns an unexpected shape of JSON. The body holds the start of the response.
+    #[error("The response from Jira doesn't match the model: {source}")]
+    Deserialize {
+        source: serde_json::Error,
+        body: String,
+    },
+    /// The replay directory has no recording of this request.
+    /// Record the request first with `RecordingTransport`.
+    #[error("No recorded response to the request: {method} {url}")]
+    MissingRecording { method: String, url: String },
+    /// The recorded response in this file can't form a response,
+    /// such as when the file has an invalid status code.
+    #[error("The recorded response in {} is invalid.", .0.display())]
+    InvalidRecording(PathBuf),
+    /// The instance can't change the options of an HTTP client that the application
+    /// provided with `JiraInstance::with_client`. Configure the client itself instead.
+    #[error(
+        "The HTTP client comes from the application, so the instance can't change its options."
+    )]
+    ExternalClient,
+    /// Jira didn't respond within the timeout of the instance,
+    /// or the connection didn't open within the connect timeout.
+    /// The server might be overloaded, so the request might succeed later.
+    #[error("The request to Jira timed out.")]
+    Timeout(#[source] reqwest::Error),
     #[error("Error in accessing the Jira REST API.")]
-    Request(#[from] reqwest::Error),
+    Request(#[source] reqwest::Error),
+    /// Reading or writing a local file failed, such as the temporary file of an export.
+    #[error("Error in accessing a local file.")]
+    Io(#[from] std::io::Error),
+    /// The JSON of an issue couldn't be written or read back.
+    #[error("Error in processing the JSON of an issue.")]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<reqwest::Error> for JiraQueryError {
+    /// Distinguish a timeout from the other failures of the request.
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            Self::Timeout(error)
+        } else {
+            Self::Request(error)
+        }
+    }
+}
+
+/// The body of an error response from the Jira REST API.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct ErrorBody {
+    #[serde(default, rename = "errorMessages")]
+    error_messages: Vec<String>,
+    /// The errors of specific fields, keyed by field ID.
+    #[serde(default)]
+    errors: BTreeMap<String, String>,
+}
+
+impl ErrorBody {
+    /// The messages in the body of an error response, joined into a single line.
+    /// Falls back to the body as it is if it isn't the JSON of an error response.
+    pub(crate) fn explain(body: &str) -> String {
+        let parsed = serde_json::from_str::<Self>(body).unwrap_or_default();
+        let messages: Vec<String> = parsed
+            .error_messages
+            .into_iter()
+            .chain(
+                parsed
+                    .errors
+                    .into_iter()
+                    .map(|(field, error)| format!("{field}: {error}")),
+            )
+            .collect();
+
+        if messages.is_empty() {
+            body.to_string()
+        } else {
+            messages.join(" ")
+        }
+    }
+}
+
+impl JiraQueryError {
+    /// Classify a 404 response about the issue or project with this key.
+    ///
+    /// Jira explains a missing project in the messages, such as
+    /// `No project could be found with key 'TEST'.` Any other 404, including
+    /// `Issue Does Not Exist` and a response without messages, concerns the issue.
+    pub(crate) fn not_found(key: &str, body: &ErrorBody) -> Self {
+        let about_project = body
+            .error_messages
+            .iter()
+            .any(|message| message.to_lowercase().contains("no project"));
+
+        if about_project {
+            Self::ProjectNotFound {
+                key: key.to_string(),
+            }
+        } else {
+            Self::NotFound {
+                key: key.to_string(),
+            }
+        }
+    }
 }
@@ -0,0 +1,261 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Large exports of search results, which move to a temporary file on disk
+//! when they grow past a limit, rather than holding every issue in memory.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::access::{JiraInstance, Method, Pagination, SearchOptions};
+use crate::errors::JiraQueryError;
+use crate::issue_model::Issue;
+
+// The page size of an export, unless the instance paginates with its own chunk size.
+const EXPORT_CHUNK_SIZE: u32 = 100;
+
+// Distinguishes the temporary files of several exports in the same process.
+static EXPORT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// The number of names to try for the temporary file if the previous ones already exist.
+const TEMPORARY_FILE_ATTEMPTS: u32 = 100;
+
+/// The issues from an export, either in memory or in a temporary NDJSON file
+/// with one issue per line. The file is deleted when the export is dropped.
+#[derive(Debug)]
+pub struct IssueExport {
+    count: usize,
+    storage: Storage,
+}
+
+#[derive(Debug)]
+enum Storage {
+    Memory(Vec<Issue>),
+    File(PathBuf),
+}
+
+impl IssueExport {
+    /// The number of issues in the export.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Whether the export contains no issues.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The temporary file that stores the issues, if the export moved to disk.
+    #[must_use]
+    pub fn file(&self) -> Option<&Path> {
+        match &self.storage {
+            Storage::Memory(_) => None,
+            Storage::File(path) => Some(path),
+        }
+    }
+
+    /// Read the issues in the order of the search results. An export on disk
+    /// parses each issue only when the iterator reaches it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the temporary file can't be opened. Each item fails
+    /// if its line can't be read or doesn't match the issue model.
+    pub fn issues(
+        &self,
+    ) -> Result<Box<dyn Iterator<Item = Result<Issue, JiraQueryError>> + '_>, JiraQueryError> {
+        match &self.storage {
+            Storage::Memory(issues) => Ok(Box::new(issues.iter().cloned().map(Ok))),
+            Storage::File(path) => {
+                let reader = BufReader::new(File::open(path)?);
+                Ok(Box::new(reader.lines().map(|line| {
+                    let issue = serde_json::from_str(&line?)?;
+                    Ok(issue)
+                })))
+            }
+        }
+    }
+}
+
+impl Drop for IssueExport {
+    fn drop(&mut self) {
+        if let Storage::File(path) = &self.storage {
+            if let Err(error) = std::fs::remove_file(path) {
+                log::warn!(
+                    "Failed to remove the export file {}: {error}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Collects the issues in memory until they exceed the limit, and then in a file.
+struct Spill {
+    memory_cap: usize,
+    count: usize,
+    issues: Vec<Issue>,
+    file: Option<(PathBuf, BufWriter<File>)>,
+}
+
+impl Spill {
+    fn push(&mut self, issue: Issue) -> Result<(), JiraQueryError> {
+        self.count += 1;
+
+        if self.file.is_none() && self.issues.len() < self.memory_cap {
+            self.issues.push(issue);
+            return Ok(());
+        }
+
+        if self.file.is_none() {
+            let (path, file) = create_temporary_file()?;
+            log::debug!("Moving the export to {}", path.display());
+            let mut writer = BufWriter::new(file);
+            for issue in self.issues.drain(..) {
+                write_line(&mut writer, &issue)?;
+            }
+            self.file = Some((path, writer));
+        }
+
+        if let Some((_, writer)) = &mut self.file {
+            write_line(writer, &issue)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<IssueExport, JiraQueryError> {
+        // If flushing fails, dropping the spill removes the incomplete file.
+        if let Some((_, writer)) = &mut self.file {
+            writer.flush()?;
+        }
+
+        let storage = match self.file.take() {
+            Some((path, _)) => Storage::File(path),
+            None => Storage::Memory(std::mem::take(&mut self.issues)),
+        };
+
+        Ok(IssueExport {
+            count: self.count,
+            storage,
+        })
+    }
+}
+
+impl Drop for Spill {
+    /// Remove the file of an unfinished export, such as when a request fails.
+    fn drop(&mut self) {
+        if let Some((path, _)) = &self.file {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Write the issue as a single line of JSON.
+fn write_line(writer: &mut impl Write, issue: &Issue) -> Result<(), JiraQueryError> {
+    serde_json::to_writer(&mut *writer, issue)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Create a new file in the temporary directory, which only the current user can read.
+///
+/// The temporary directory is shared with other users, who could otherwise prepare a file
+/// or a symbolic link at the predictable path. The file must not exist yet,
+/// so if the path is taken, the function tries another one.
+fn create_temporary_file() -> Result<(PathBuf, File), JiraQueryError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    for _ in 0..TEMPORARY_FILE_ATTEMPTS {
+        let path = temporary_path();
+        match options.open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                log::debug!("The temporary file {} already exists.", path.display());
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        "No free name for the temporary file of the export.",
+    )
+    .into())
+}
+
+/// A unique path of a new file in the temporary directory.
+fn temporary_path() -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos());
+    let counter = EXPORT_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "jira_query-{}-{nanos}-{counter}.ndjson",
+        std::process::id()
+    ))
+}
+
+impl JiraInstance {
+    /// Access all issues that match a free-form JQL search, page by page.
+    ///
+    /// Up to `memory_cap` issues stay in memory. If the search returns more,
+    /// the export moves all issues to a temporary NDJSON file, which you can read back
+    /// with `IssueExport::issues` one issue at a time.
+    ///
+    /// The export uses the chunk size of the instance pagination if it's set,
+    /// and otherwise pages of 100 issues.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails, if the response doesn't match the issue model,
+    /// or if the temporary file can't be written.
+    pub async fn export(
+        &self,
+        query: &str,
+        memory_cap: usize,
+    ) -> Result<IssueExport, JiraQueryError> {
+        let chunk_size = match self.pagination {
+            Pagination::ChunkSize(size) => size,
+            _ => EXPORT_CHUNK_SIZE,
+        };
+        let options = SearchOptions::new().max_results(chunk_size);
+
+        let mut spill = Spill {
+            memory_cap,
+            count: 0,
+            issues: Vec::new(),
+            file: None,
+        };
+        self.paginate_into(&Method::Search(query), &options, chunk_size, |issue| {
+            spill.push(issue)
+        })
+        .await?;
+
+        spill.finish()
+    }
+}
@@ -0,0 +1,188 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Export the links between issues as a graph for visualization.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::issue_model::Issue;
+
+/// Format the links between the issues as a Graphviz DOT digraph.
+///
+/// Each edge points in the outward direction of the link and carries the outward
+/// description of the link type as its label, such as `TEST-1 -> TEST-2 [label="blocks"]`.
+/// A link between two issues in the set appears only once, even though Jira lists it
+/// on both issues.
+///
+/// Linked issues that aren't in the set appear as dashed leaf nodes,
+/// without their own links.
+#[must_use]
+pub fn links_to_dot(issues: &[Issue]) -> String {
+    // The label of every node, by issue key. Sorted for a stable output.
+    let mut nodes: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut leaves: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut edges: BTreeSet<(&str, &str, &str)> = BTreeSet::new();
+
+    for issue in issues {
+        nodes.insert(&issue.key, &issue.fields.summary);
+    }
+
+    for issue in issues {
+        for link in &issue.fields.issuelinks {
+            let label = link.link_type.outward.as_str();
+            if let Some(outward) = &link.outward_issue {
+                edges.insert((&issue.key, &outward.key, label));
+                leaves.insert(&outward.key, &outward.fields.summary);
+            }
+            if let Some(inward) = &link.inward_issue {
+                edges.insert((&inward.key, &issue.key, label));
+                leaves.insert(&inward.key, &inward.fields.summary);
+            }
+        }
+    }
+
+    let mut dot = String::from("digraph issues {\n");
+
+    for (key, summary) in &nodes {
+        dot.push_str(&node(key, summary, ""));
+    }
+    for (key, summary) in &leaves {
+        if !nodes.contains_key(key) {
+            dot.push_str(&node(key, summary, ", style=dashed"));
+        }
+    }
+    for (from, to, label) in &edges {
+        dot.push_str(&edge(from, to, label));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+/// A single node statement, labeled with the key and the summary of the issue.
+fn node(key: &str, summary: &str, attributes: &str) -> String {
+    format!(
+        "    \"{}\" [label=\"{}\\n{}\"{}];\n",
+        escape(key),
+        escape(key),
+        escape(summary),
+        attributes
+    )
+}
+
+/// A single edge statement, labeled with the link type.
+fn edge(from: &str, to: &str, label: &str) -> String {
+    format!(
+        "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+        escape(from),
+        escape(to),
+        escape(label)
+    )
+}
+
+/// Escape a string for a quoted DOT identifier.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::issue_model::{Fields, IssueLink, IssueLinkType, LinkedIssue, LinkedIssueFields};
+
+    fn linked(key: &str, summary: &str) -> LinkedIssue {
+        LinkedIssue {
+            key: key.to_string(),
+            fields: LinkedIssueFields {
+                summary: summary.to_string(),
+                ..LinkedIssueFields::default()
+            },
+            ..LinkedIssue::default()
+        }
+    }
+
+    fn issue(key: &str, summary: &str, issuelinks: Vec<IssueLink>) -> Issue {
+        Issue {
+            key: key.to_string(),
+            fields: Fields {
+                summary: summary.to_string(),
+                issuelinks,
+                ..Fields::default()
+            },
+            ..Issue::default()
+        }
+    }
+
+    /// Export a link between two issues in the set and a link to an issue outside it.
+    #[test]
+    fn export_links() {
+        let blocks = IssueLinkType {
+            name: "Blocks".to_string(),
+            inward: "is blocked by".to_string(),
+            outward: "blocks".to_string(),
+            ..IssueLinkType::default()
+        };
+        let relates = IssueLinkType {
+            name: "Relates".to_string(),
+            inward: "relates to".to_string(),
+            outward: "relates to".to_string(),
+            ..IssueLinkType::default()
+        };
+
+        let issues = [
+            issue(
+                "TEST-1",
+                "Fix the \"login\" page",
+                vec![IssueLink {
+                    outward_issue: Some(linked("TEST-2", "Release")),
+                    link_type: blocks.clone(),
+                    ..IssueLink::default()
+                }],
+            ),
+            issue(
+                "TEST-2",
+                "Release",
+                vec![
+                    IssueLink {
+                        inward_issue: Some(linked("TEST-1", "Fix the \"login\" page")),
+                        link_type: blocks,
+                        ..IssueLink::default()
+                    },
+                    IssueLink {
+                        outward_issue: Some(linked("OTHER-7", "Update the docs")),
+                        link_type: relates,
+                        ..IssueLink::default()
+                    },
+                ],
+            ),
+        ];
+
+        let dot = links_to_dot(&issues);
+
+        assert!(dot.starts_with("digraph issues {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("\"TEST-1\" [label=\"TEST-1\\nFix the \\\"login\\\" page\"];"));
+        assert!(dot.contains("\"OTHER-7\" [label=\"OTHER-7\\nUpdate the docs\", style=dashed];"));
+        assert_eq!(
+            dot.matches("\"TEST-1\" -> \"TEST-2\" [label=\"blocks\"];")
+                .count(),
+            1
+        );
+        assert!(dot.contains("\"TEST-2\" -> \"OTHER-7\" [label=\"relates to\"];"));
+    }
+}
@@ -14,24 +14,78 @@ See the License for the specific language governing permissions and
 limitations under the License.
 */
 
-use chrono::{DateTime, NaiveDate, Utc};
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::hash::BuildHasher;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
 /// This module replicates the fields in a Jira issue as strongly typed structs.
 /// Any extra fields that come from a custom Jira configuration are captured
 /// in the `extra` hash map in the parent struct.
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use unicode_width::UnicodeWidthStr;
+
+use crate::adf::Body;
+use crate::agile::Sprint;
 
 /// The response from Jira to a JQL query,
 /// which includes the list of requested issues and additional metadata.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct JqlResults {
     pub issues: Vec<Issue>,
+    #[serde(rename = "maxResults")]
+    pub max_results: i32,
+    #[serde(rename = "startAt")]
+    pub start_at: i32,
+    pub total: i32,
+    /// The display names of the fields in the results, keyed by the field ID.
+    /// Only present if the request expanded `names`, such as with `SearchOptions::names`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub names: Option<HashMap<String, String>>,
+    /// The types of the fields in the results, keyed by the field ID.
+    /// Only present if the request expanded `schema`, such as with `SearchOptions::schema`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<HashMap<String, FieldSchema>>,
     #[serde(flatten)]
     pub extra: Value,
 }
 
+impl JqlResults {
+    /// The ID of the field with this display name, such as `customfield_10016`
+    /// for `Story Points`. The names compare case-insensitively.
+    ///
+    /// Returns `None` if the request didn't expand `names`, or if no field has the name.
+    /// If several custom fields share the name, the result is any one of them.
+    #[must_use]
+    pub fn field_id(&self, name: &str) -> Option<&str> {
+        self.names
+            .as_ref()?
+            .iter()
+            .find(|(_, display_name)| display_name.eq_ignore_ascii_case(name))
+            .map(|(id, _)| id.as_str())
+    }
+}
+
 /// A single Jira issue with all its fields.
+///
+/// With the `test-util` feature, `Issue` and all nested structs implement `Default`,
+/// so you can construct a partial issue in your tests:
+///
+/// ```ignore
+/// let issue = Issue {
+///     key: "TEST-1".to_string(),
+///     fields: Fields {
+///         summary: "A test issue".to_string(),
+///         ..Fields::default()
+///     },
+///     ..Issue::default()
+/// };
+/// ```
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Issue {
     pub id: String,
     pub key: String,
@@ -39,18 +93,274 @@ pub struct Issue {
     pub fields: Fields,
     #[serde(rename = "self")]
     pub self_link: String,
+    /// The entity properties of the issue, by their keys. Only present if the request
+    /// asked for them, such as with `SearchOptions::properties`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<HashMap<String, Value>>,
+    /// The fields rendered as HTML. Only present if the request expanded `renderedFields`.
+    #[serde(rename = "renderedFields", skip_serializing_if = "Option::is_none")]
+    pub rendered_fields: Option<RenderedFields>,
+    /// Each field in several representations, keyed by the field ID and then by the version
+    /// of the representation. Only present if the request expanded `versionedRepresentations`,
+    /// an advanced option of Jira Cloud.
+    #[serde(
+        rename = "versionedRepresentations",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub versioned_representations: Option<HashMap<String, HashMap<String, Value>>>,
+    /// The change history of the issue. Only present if the request expanded `changelog`,
+    /// such as with `SearchOptions::changelog`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<Changelog>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// Follow the segments of a dotted path through objects and arrays.
+fn walk_path<'a>(mut current: &'a Value, segments: &[&str]) -> Option<&'a Value> {
+    for segment in segments {
+        current = match current {
+            Value::Object(map) => map.get(*segment)?,
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+impl Issue {
+    /// The URL of the issue in the Jira web UI, derived from its REST `self` link.
+    /// Returns `None` if the link isn't a REST URL.
+    #[must_use]
+    pub fn browse_url(&self) -> Option<String> {
+        browse_url(&self.self_link, &self.key)
+    }
+
+    /// The value of the field in the representation with this version, such as `"1"` for the raw
+    /// text and `"2"` for the Atlassian Document Format of a rich text field on Jira Cloud.
+    ///
+    /// Returns `None` unless the request expanded `versionedRepresentations`,
+    /// or if the field doesn't have the representation.
+    #[must_use]
+    pub fn field_representation(&self, field_id: &str, format: &str) -> Option<&Value> {
+        self.versioned_representations
+            .as_ref()?
+            .get(field_id)?
+            .get(format)
+    }
+
+    /// The description for display: the rendered HTML if the issue has rendered fields,
+    /// and otherwise the raw description, or the plain text of an ADF description.
+    /// Edit the raw `Fields::description` instead.
+    #[must_use]
+    pub fn display_description(&self) -> Option<Cow<'_, str>> {
+        if let Some(rendered) = self
+            .rendered_fields
+            .as_ref()
+            .and_then(|rendered| rendered.description.as_deref())
+        {
+            return Some(Cow::Borrowed(rendered));
+        }
+
+        match self.fields.description.as_ref()? {
+            Body::Text(text) => Some(Cow::Borrowed(text)),
+            adf @ Body::Adf(_) => Some(Cow::Owned(adf.to_plain_text())),
+        }
+    }
+
+    /// Resolve a dotted path, such as `fields.status.name` or `fields.customfield_10001.value`,
+    /// against the JSON representation of the issue. Numeric segments index into arrays,
+    /// such as `fields.labels.0`.
+    ///
+    /// The path uses the original Jira field names, not the Rust field names,
+    /// and it reaches both the typed fields and the custom fields in `extra`.
+    /// Returns `None` if any segment of the path is missing.
+    ///
+    /// The typed fields have no JSON form to borrow from, so the method returns
+    /// an owned value rather than a reference. A path into the custom fields in `extra`
+    /// only clones the value that it finds. Any other path serializes the whole issue
+    /// on each call, so prefer the typed fields in code that doesn't need to select
+    /// fields at runtime.
+    #[must_use]
+    pub fn get_path(&self, path: &str) -> Option<Value> {
+        let segments: Vec<&str> = path.split('.').collect();
+
+        let extra = match segments.as_slice() {
+            ["fields", name, rest @ ..] => self.fields.extra.get(name).map(|value| (value, rest)),
+            [name, rest @ ..] => self.extra.get(name).map(|value| (value, rest)),
+            [] => None,
+        };
+        if let Some((value, rest)) = extra {
+            return walk_path(value, rest).cloned();
+        }
+
+        let json = serde_json::to_value(self).ok()?;
+        walk_path(&json, &segments).cloned()
+    }
+
+    /// The fields of the issue keyed by their human-readable names from the field metadata,
+    /// such as `Story Points` instead of `customfield_10016`.
+    /// Fields that have no metadata keep their raw ID as the key.
+    #[must_use]
+    pub fn labeled_fields(&self, meta: &[FieldMeta]) -> HashMap<String, Value> {
+        let names: HashMap<&str, &str> = meta
+            .iter()
+            .map(|field| (field.id.as_str(), field.name.as_str()))
+            .collect();
+
+        match serde_json::to_value(&self.fields) {
+            Ok(Value::Object(fields)) => fields
+                .into_iter()
+                .map(|(id, value)| {
+                    let label = names
+                        .get(id.as_str())
+                        .map_or(id.clone(), ToString::to_string);
+                    (label, value)
+                })
+                .collect(),
+            _ => HashMap::new(),
+        }
+    }
+
+    /// The values of the issue in the specified columns of an issue navigator filter,
+    /// in the same order, so that an export matches the columns in the Jira UI.
+    /// A field that the issue doesn't have results in `Value::Null`.
+    #[must_use]
+    pub fn column_values(&self, columns: &[FilterColumn]) -> Vec<Value> {
+        let json = serde_json::to_value(self).unwrap_or_default();
+
+        columns
+            .iter()
+            .map(|column| match column.value.as_str() {
+                // The issue key is a top-level attribute, not a field.
+                "issuekey" => Value::from(self.key.as_str()),
+                field => json["fields"].get(field).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// The fields that differ between this issue and a later state of it,
+    /// such as the same issue downloaded again, sorted by the field ID.
+    ///
+    /// A field that's missing on one side appears with a `null` value on that side.
+    #[must_use]
+    pub fn diff(&self, later: &Self) -> Vec<FieldChange> {
+        let fields = |issue: &Self| match serde_json::to_value(&issue.fields) {
+            Ok(Value::Object(fields)) => fields,
+            _ => serde_json::Map::new(),
+        };
+        let before = fields(self);
+        let after = fields(later);
+        let ids: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+        ids.into_iter()
+            .filter_map(|id| {
+                let from = before.get(id).cloned().unwrap_or_default();
+                let to = after.get(id).cloned().unwrap_or_default();
+                (from != to).then(|| FieldChange {
+                    field: id.clone(),
+                    from,
+                    to,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A difference in a single field between two states of an issue, from `Issue::diff`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The ID of the field, such as `summary` or `customfield_10020`.
+    pub field: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+/// The metadata of a system or custom field on the Jira instance.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct FieldMeta {
+    pub id: String,
+    pub name: String,
+    pub custom: bool,
+    pub schema: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The type of a field, as listed in the `schema` expansion of search results.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct FieldSchema {
+    /// The type of the value, such as `string`, `number`, `array`, or `user`.
+    #[serde(rename = "type")]
+    pub field_type: String,
+    /// The type of the items if the value is an array, such as `string` for labels.
+    pub items: Option<String>,
+    /// The ID of a system field, such as `summary`.
+    pub system: Option<String>,
+    /// The type key of a custom field, such as
+    /// `com.atlassian.jira.plugin.system.customfieldtypes:float`.
+    pub custom: Option<String>,
+    #[serde(rename = "customId")]
+    pub custom_id: Option<i64>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The metadata of a field on the create screen of a project and an issue type,
+/// as listed by `JiraInstance::create_meta`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct CreateFieldMeta {
+    #[serde(rename = "fieldId")]
+    pub field_id: String,
+    pub name: String,
+    pub required: bool,
+    #[serde(rename = "hasDefaultValue", default)]
+    pub has_default_value: bool,
+    /// The values that a select field accepts, such as the priorities of the project.
+    /// Missing if the field accepts any value.
+    #[serde(rename = "allowedValues")]
+    pub allowed_values: Option<Vec<Value>>,
+    pub schema: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A column in the issue navigator configuration of a Jira filter.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct FilterColumn {
+    /// The display name of the column.
+    pub label: String,
+    /// The ID of the field that the column shows.
+    pub value: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The fields of a Jira issue rendered as HTML, for display rather than editing.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct RenderedFields {
+    pub description: Option<String>,
     #[serde(flatten)]
     pub extra: Value,
 }
 
 /// A container for most fields of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Fields {
     #[serde(rename = "lastViewed")]
     pub last_viewed: Option<DateTime<Utc>>,
     pub labels: Vec<String>,
     pub assignee: Option<User>,
-    pub description: Option<String>,
+    pub description: Option<Body>,
+    // Jira sends the due date only as `YYYY-MM-DD`, unlike `resolutiondate`,
+    // which has a time component.
     pub duedate: Option<NaiveDate>,
     // Both `versions` and `fixVersions` are optional fields and they might
     // either be missing or set to an empty list.
@@ -61,31 +371,45 @@ pub struct Fields {
     #[serde(default)]
     #[serde(rename = "fixVersions")]
     pub fix_versions: Vec<Version>,
-    pub reporter: User,
+    // Issues imported from other trackers or created anonymously might have no reporter
+    // or creator, which Jira reports as `null`.
+    pub reporter: Option<User>,
     pub status: Status,
     pub created: DateTime<Utc>,
     pub updated: DateTime<Utc>,
     pub issuetype: IssueType,
-    pub timeestimate: Option<i32>,
-    pub aggregatetimeestimate: Option<i32>,
-    pub timeoriginalestimate: Option<i32>,
-    pub timespent: Option<i32>,
-    pub aggregatetimespent: Option<i32>,
-    pub aggregatetimeoriginalestimate: Option<i32>,
+    // The time tracking fields are in seconds. Some instances return them
+    // as floating-point numbers or as strings.
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub timeestimate: Option<i64>,
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub aggregatetimeestimate: Option<i64>,
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub timeoriginalestimate: Option<i64>,
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub timespent: Option<i64>,
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub aggregatetimespent: Option<i64>,
+    #[serde(default, deserialize_with = "optional_seconds")]
+    pub aggregatetimeoriginalestimate: Option<i64>,
     pub progress: Progress,
     pub aggregateprogress: Progress,
     pub workratio: i32,
     pub summary: String,
-    pub creator: User,
+    pub creator: Option<User>,
     pub project: Project,
     pub priority: Option<Priority>,
     pub components: Vec<Component>,
     pub watches: Watches,
     pub archiveddate: Option<DateTime<Utc>>,
-    pub archivedby: Option<DateTime<Utc>>,
+    pub archivedby: Option<User>,
     pub resolution: Option<Resolution>,
     pub resolutiondate: Option<DateTime<Utc>>,
     pub comment: Option<Comments>,
+    /// The work logged on the issue. Only present if the request included the `worklog` field.
+    pub worklog: Option<Worklogs>,
+    #[serde(default)]
+    pub attachment: Vec<Attachment>,
     pub issuelinks: Vec<IssueLink>,
     pub votes: Votes,
     pub parent: Option<CondensedIssue>,
@@ -96,16 +420,250 @@ pub struct Fields {
     pub extra: Value,
 }
 
+impl Fields {
+    /// The most recently created comment among the loaded comments, if any.
+    #[must_use]
+    pub fn latest_comment(&self) -> Option<&Comment> {
+        self.comment
+            .as_ref()?
+            .comments
+            .iter()
+            .max_by_key(|comment| comment.created)
+    }
+
+    /// The ratio of the logged work to the original estimate, as a percentage.
+    /// Returns `None` if the issue has no estimate, which Jira reports as `-1`.
+    #[must_use]
+    pub const fn work_ratio(&self) -> Option<i32> {
+        if self.workratio == -1 {
+            None
+        } else {
+            Some(self.workratio)
+        }
+    }
+
+    /// The share of the work that's done, from `0.0` to `1.0`, based on the time tracking.
+    ///
+    /// With `include_subtasks`, the ratio uses `aggregateprogress`, which adds up the issue
+    /// and its subtasks, such as for a parent issue. Otherwise, it uses `progress`,
+    /// which only counts the issue itself. Returns `None` if the progress has no total,
+    /// such as when nobody estimated or logged any work.
+    // The conversion only loses precision beyond 2^53 seconds, far beyond any estimate.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn completion_ratio(&self, include_subtasks: bool) -> Option<f64> {
+        let progress = if include_subtasks {
+            &self.aggregateprogress
+        } else {
+            &self.progress
+        };
+
+        if progress.total > 0 {
+            Some(progress.progress as f64 / progress.total as f64)
+        } else {
+            None
+        }
+    }
+
+    /// The remaining estimate of the issue, from `timeestimate`.
+    /// See `Fields::time_spent_duration` for the handling of invalid values.
+    #[must_use]
+    pub fn time_estimate_duration(&self) -> Option<Duration> {
+        self.timeestimate.and_then(seconds_to_duration)
+    }
+
+    /// The remaining estimate of the issue and its subtasks, from `aggregatetimeestimate`.
+    #[must_use]
+    pub fn aggregate_time_estimate_duration(&self) -> Option<Duration> {
+        self.aggregatetimeestimate.and_then(seconds_to_duration)
+    }
+
+    /// The original estimate of the issue, from `timeoriginalestimate`.
+    #[must_use]
+    pub fn time_original_estimate_duration(&self) -> Option<Duration> {
+        self.timeoriginalestimate.and_then(seconds_to_duration)
+    }
+
+    /// The original estimate of the issue and its subtasks,
+    /// from `aggregatetimeoriginalestimate`.
+    #[must_use]
+    pub fn aggregate_time_original_estimate_duration(&self) -> Option<Duration> {
+        self.aggregatetimeoriginalestimate
+            .and_then(seconds_to_duration)
+    }
+
+    /// The work logged on the issue, from `timespent`.
+    ///
+    /// Returns `None` if the field is empty, or if its value is negative, which Jira
+    /// never produces for valid data. A zero value, such as an estimate that the work
+    /// used up, is a zero duration.
+    #[must_use]
+    pub fn time_spent_duration(&self) -> Option<Duration> {
+        self.timespent.and_then(seconds_to_duration)
+    }
+
+    /// The work logged on the issue and its subtasks, from `aggregatetimespent`.
+    #[must_use]
+    pub fn aggregate_time_spent_duration(&self) -> Option<Duration> {
+        self.aggregatetimespent.and_then(seconds_to_duration)
+    }
+
+    /// Whether the issue belongs to no component, a common triage check.
+    #[must_use]
+    pub fn has_no_component(&self) -> bool {
+        self.components.is_empty()
+    }
+
+    /// Whether the issue targets no fix version, a common triage check.
+    #[must_use]
+    pub fn has_no_fix_version(&self) -> bool {
+        self.fix_versions.is_empty()
+    }
+
+    /// The time of the last update in the time zone of the viewer, rather than in UTC.
+    ///
+    /// The time zone can be any `chrono` time zone, such as `chrono::Local`,
+    /// a `chrono::FixedOffset`, or a named zone from the `chrono-tz` crate.
+    #[must_use]
+    pub fn updated_in_tz<Tz: TimeZone>(&self, tz: &Tz) -> DateTime<Tz> {
+        self.updated.with_timezone(tz)
+    }
+
+    /// The lead time of a resolved issue: the time between its creation and its resolution.
+    /// Returns `None` if the issue isn't resolved.
+    #[must_use]
+    pub fn resolution_sla(&self) -> Option<Duration> {
+        self.resolutiondate
+            .map(|resolved| resolved.signed_duration_since(self.created))
+    }
+
+    /// Whether Jira returned the field with this ID, such as `duedate` or `customfield_10001`.
+    ///
+    /// Jira silently omits the fields that the current user lacks permission to see,
+    /// which this method can distinguish from empty fields for the custom fields in `extra`:
+    /// an empty custom field is present with a `null` value, while a hidden one is missing.
+    ///
+    /// For the typed fields, an empty optional field and a hidden optional field both parse
+    /// as `None`, so this method reports both as not visible. To learn whether such a field
+    /// is editable or hidden, combine this method with the edit metadata of the issue.
+    #[must_use]
+    pub fn is_field_visible(&self, field_id: &str) -> bool {
+        if self.extra.get(field_id).is_some() {
+            return true;
+        }
+
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|json| json.get(field_id).cloned())
+            .map_or(false, |value| !value.is_null())
+    }
+
+    /// The customer request type of a Jira Service Management issue,
+    /// stored in the custom field with this ID, such as `customfield_10010`.
+    ///
+    /// Returns `None` if the field is missing or doesn't contain a request type.
+    #[must_use]
+    pub fn request_type(&self, field_id: &str) -> Option<RequestType> {
+        let value = self.extra.get(field_id)?.get("requestType")?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// The request participants of a Jira Service Management issue,
+    /// stored in the custom field with this ID.
+    ///
+    /// Returns an empty list if the field is missing or doesn't contain users.
+    #[must_use]
+    pub fn request_participants(&self, field_id: &str) -> Vec<User> {
+        self.extra
+            .get(field_id)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// The number of terminal columns that the summary takes up, such as to align
+    /// the columns of a table. Wide characters, such as CJK ideographs and most emoji,
+    /// take two columns, and zero-width characters, such as combining marks
+    /// and the zero-width joiner, take none.
+    ///
+    /// The width follows the `unicode-width` crate, which implements the East Asian Width
+    /// property of Unicode and the emoji presentation rules.
+    #[must_use]
+    pub fn summary_display_width(&self) -> usize {
+        UnicodeWidthStr::width(self.summary.as_str())
+    }
+
+    /// The sprints of the issue, from the sprint custom field with this ID,
+    /// such as `customfield_10020`. The ID differs between instances.
+    ///
+    /// Returns an empty list if the issue isn't in any sprint.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value of the field isn't a list of sprints.
+    pub fn sprints(&self, field_id: &str) -> Result<Vec<Sprint>, serde_json::Error> {
+        match self.extra.get(field_id) {
+            None | Some(Value::Null) => Ok(Vec::new()),
+            Some(value) => Sprint::from_field(value),
+        }
+    }
+
+    /// The story points of the issue, from the custom field with this ID,
+    /// such as `customfield_10016`. The ID differs between instances.
+    ///
+    /// Returns `None` if the issue has no estimate in story points.
+    #[must_use]
+    pub fn story_points(&self, field_id: &str) -> Option<f64> {
+        self.extra.get(field_id)?.as_f64()
+    }
+
+    /// The value of the custom field with this ID, such as `customfield_10020`,
+    /// deserialized into the type of your choice.
+    ///
+    /// Returns `Ok(None)` if the field is missing or if its value is `null`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value of the field doesn't match the type.
+    pub fn custom_field<T: DeserializeOwned>(
+        &self,
+        id: &str,
+    ) -> Result<Option<T>, serde_json::Error> {
+        typed_field(self.extra.get(id))
+    }
+
+    /// Whether the issue is flagged as an impediment, which Jira stores as the `Impediment`
+    /// option of a multi-select custom field with this ID.
+    ///
+    /// The ID differs between instances. Find it in `JiraInstance::field_metadata`
+    /// as the custom field named `Flagged`.
+    #[must_use]
+    pub fn is_flagged(&self, field_id: &str) -> bool {
+        self.extra
+            .get(field_id)
+            .and_then(Value::as_array)
+            .map_or(false, |options| {
+                options.iter().any(|option| option["value"] == "Impediment")
+            })
+    }
+}
+
 /// The representation of a Jira user account.
+///
+/// Jira Server identifies users by `key` and `name`, while Jira Cloud
+/// identifies them by `account_id`. Use `User::same_as` to compare users
+/// regardless of the deployment type.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct User {
     pub active: bool,
     #[serde(rename = "displayName")]
     pub display_name: String,
     #[serde(rename = "emailAddress")]
     pub email_address: Option<String>,
-    pub key: String,
-    pub name: String,
+    #[serde(rename = "accountId")]
+    pub account_id: Option<String>,
+    pub key: Option<String>,
+    pub name: Option<String>,
     #[serde(rename = "timeZone")]
     pub time_zone: String,
     #[serde(rename = "avatarUrls")]
@@ -116,8 +674,47 @@ pub struct User {
     pub extra: Value,
 }
 
+impl User {
+    /// Whether both users are the same account. Compares the account IDs if both users
+    /// have one, or else the keys if both have one, or else the names.
+    /// Users without any common identifier aren't the same.
+    #[must_use]
+    pub fn same_as(&self, other: &User) -> bool {
+        fn both<'a>(a: Option<&'a str>, b: Option<&'a str>) -> Option<(&'a str, &'a str)> {
+            Some((a?, b?))
+        }
+
+        if let Some((a, b)) = both(self.account_id.as_deref(), other.account_id.as_deref()) {
+            a == b
+        } else if let Some((a, b)) = both(self.key.as_deref(), other.key.as_deref()) {
+            a == b
+        } else if let Some((a, b)) = both(self.name.as_deref(), other.name.as_deref()) {
+            a == b
+        } else {
+            false
+        }
+    }
+
+    /// The domain of the email address, such as `example.com` for `jdoe@example.com`.
+    ///
+    /// Returns `None` if Jira hides the email address, which is common on Jira Cloud
+    /// because of the user's privacy settings, or if the address has no domain.
+    #[must_use]
+    pub fn email_domain(&self) -> Option<&str> {
+        let (_, domain) = self.email_address.as_deref()?.rsplit_once('@')?;
+        let domain = domain.trim();
+
+        if domain.is_empty() {
+            None
+        } else {
+            Some(domain)
+        }
+    }
+}
+
 /// The representation of a Jira product version.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Version {
     pub id: String,
     pub description: Option<String>,
@@ -133,8 +730,163 @@ pub struct Version {
     pub extra: Value,
 }
 
+impl Version {
+    /// Whether the version has a release date in the past, but hasn't been released yet.
+    #[must_use]
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        !self.released && self.release_date.map_or(false, |date| date < today)
+    }
+
+    /// Classify the version for release dashboards, as of the `today` date.
+    ///
+    /// An archived version is always `Archived`, regardless of its release state.
+    #[must_use]
+    pub fn status(&self, today: NaiveDate) -> VersionStatus {
+        if self.archived {
+            VersionStatus::Archived
+        } else if self.released {
+            VersionStatus::Released
+        } else if self.is_overdue(today) {
+            VersionStatus::Overdue
+        } else {
+            VersionStatus::Unreleased
+        }
+    }
+}
+
+/// The release state of a Jira product version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VersionStatus {
+    Unreleased,
+    Released,
+    Archived,
+    Overdue,
+}
+
+/// The issues that block the release of the version with this name:
+/// the issues that have the version as a fix version, but aren't done yet.
+///
+/// An issue is done when its status belongs to the `done` status category.
+#[must_use]
+pub fn unreleased_blockers<'a>(issues: &'a [Issue], version_name: &str) -> Vec<&'a Issue> {
+    issues
+        .iter()
+        .filter(|issue| {
+            issue
+                .fields
+                .fix_versions
+                .iter()
+                .any(|version| version.name == version_name)
+        })
+        .filter(|issue| issue.fields.status.status_category.key != "done")
+        .collect()
+}
+
+/// Group the issues by their labels that start with the prefix, such as `team:`,
+/// with the rest of the label after the prefix as the key of the group.
+///
+/// An issue with several matching labels belongs to each of their groups.
+/// Issues without any matching label don't appear in the result.
+#[must_use]
+pub fn group_by_label<'a>(
+    issues: &'a [Issue],
+    label_prefix: &str,
+) -> HashMap<String, Vec<&'a Issue>> {
+    let mut groups: HashMap<String, Vec<&'a Issue>> = HashMap::new();
+
+    for issue in issues {
+        for label in &issue.fields.labels {
+            if let Some(group) = label.strip_prefix(label_prefix) {
+                let members = groups.entry(group.to_string()).or_default();
+                // Skip a label listed twice on the same issue, which was added last.
+                if members
+                    .last()
+                    .map_or(true, |last| !std::ptr::eq(*last, issue))
+                {
+                    members.push(issue);
+                }
+            }
+        }
+    }
+
+    groups
+}
+
+/// The board column of the issue, based on a mapping of status names to column names.
+///
+/// If the mapping doesn't cover the status of the issue, the column falls back
+/// to the name of the status category, such as `In Progress`.
+/// Returns `None` only if the status is unmapped and the category has no name.
+#[must_use]
+pub fn column_of<'a, S: BuildHasher>(
+    issue: &'a Issue,
+    mapping: &'a HashMap<String, String, S>,
+) -> Option<&'a str> {
+    let status = &issue.fields.status;
+
+    match mapping.get(&status.name) {
+        Some(column) => Some(column),
+        None if status.status_category.name.is_empty() => None,
+        None => Some(&status.status_category.name),
+    }
+}
+
+/// Count the issues that carry each label, sorted from the most frequent label.
+/// Labels with the same count are sorted alphabetically.
+///
+/// A label listed twice on the same issue counts once.
+#[must_use]
+pub fn label_frequencies(issues: &[Issue]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+
+    for issue in issues {
+        let labels: HashSet<&str> = issue.fields.labels.iter().map(String::as_str).collect();
+        for label in labels {
+            *counts.entry(label).or_default() += 1;
+        }
+    }
+
+    let mut frequencies: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(label, count)| (label.to_string(), count))
+        .collect();
+    frequencies.sort_by(|(label_a, count_a), (label_b, count_b)| {
+        count_b.cmp(count_a).then_with(|| label_a.cmp(label_b))
+    });
+
+    frequencies
+}
+
+/// Combine the issues from the results of several JQL queries into their union.
+/// Issues that appear in more than one result set are kept only once, by key,
+/// in the order in which they first appear.
+#[must_use]
+pub fn merge_results(results: impl IntoIterator<Item = JqlResults>) -> Vec<Issue> {
+    let mut seen_keys = HashSet::new();
+
+    results
+        .into_iter()
+        .flat_map(|result| result.issues)
+        .filter(|issue| seen_keys.insert(issue.key.clone()))
+        .collect()
+}
+
+/// The customer request type of a Jira Service Management issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct RequestType {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "serviceDeskId")]
+    pub service_desk_id: Option<String>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
 /// The Jira issue status.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Status {
     pub description: String,
     #[serde(rename = "iconUrl")]
@@ -151,6 +903,7 @@ pub struct Status {
 
 /// The category of a Jira issue status.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct StatusCategory {
     #[serde(rename = "colorName")]
     pub color_name: String,
@@ -163,8 +916,26 @@ pub struct StatusCategory {
     pub extra: Value,
 }
 
+impl StatusCategory {
+    /// A hex RGB color code, such as `#36B37E`, that approximates the Jira color name
+    /// of this category. Unknown color names fall back to a neutral gray.
+    #[must_use]
+    pub fn color_hex(&self) -> &'static str {
+        match self.color_name.as_str() {
+            "blue-gray" => "#42526E",
+            "yellow" => "#FFAB00",
+            "green" => "#36B37E",
+            "medium-gray" => "#97A0AF",
+            "warm-red" => "#DE350B",
+            "brown" => "#815B3A",
+            _ => "#7A869A",
+        }
+    }
+}
+
 /// The resolution of a Jira issue when it's closed.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Resolution {
     pub description: String,
     pub id: String,
@@ -175,11 +946,48 @@ pub struct Resolution {
     pub extra: Value,
 }
 
+/// A workflow transition that moves a Jira issue to another status.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Transition {
+    pub id: String,
+    pub name: String,
+    pub to: Status,
+    /// The fields that you can set during the transition, keyed by field ID.
+    /// Jira only lists them if you request the `transitions.fields` expansion.
+    #[serde(default)]
+    pub fields: HashMap<String, Value>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A page of the create screen fields of a project and an issue type.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CreateMetaPage {
+    pub values: Vec<CreateFieldMeta>,
+    #[serde(rename = "isLast", default = "default_true")]
+    pub is_last: bool,
+}
+
+/// Treat a page without the `isLast` attribute as the last one.
+const fn default_true() -> bool {
+    true
+}
+
+/// The response from Jira that lists the available transitions of an issue.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Transitions {
+    pub transitions: Vec<Transition>,
+}
+
 /// The type of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct IssueType {
     #[serde(rename = "avatarId")]
     pub avatar_id: Option<i32>,
+    // Some issue types in the instance-wide listing have no description.
+    #[serde(default)]
     pub description: String,
     #[serde(rename = "iconUrl")]
     pub icon_url: String,
@@ -192,12 +1000,38 @@ pub struct IssueType {
     pub extra: Value,
 }
 
+impl IssueType {
+    /// The URL of the issue type icon at a size of at least `px` pixels, such as for
+    /// high-density displays. Jira serves avatar icons in a few named sizes, from `xsmall`
+    /// at 16 pixels to `xxxlarge` at 128 pixels, so the method picks the smallest size
+    /// that fits, up to the largest one.
+    ///
+    /// Returns the URL unchanged if it has no `size` parameter,
+    /// such as the static icon of an issue type on an older Jira Server.
+    #[must_use]
+    pub fn icon_url_sized(&self, px: u32) -> String {
+        with_avatar_size(&self.icon_url, px)
+    }
+}
+
+/// The full project object as far as it lists the issue types of the project.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct ProjectIssueTypes {
+    #[serde(rename = "issueTypes")]
+    pub issue_types: Vec<IssueType>,
+}
+
 /// A project namespace that groups Jira issues.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Project {
     pub id: String,
     pub key: String,
     pub name: String,
+    // The project in an issue is reduced, so the lead and the description
+    // are only available in the full project object.
+    pub description: Option<String>,
+    pub lead: Option<User>,
     #[serde(rename = "projectTypeKey")]
     pub project_type_key: String,
     #[serde(rename = "projectCategory")]
@@ -212,6 +1046,7 @@ pub struct Project {
 
 /// The category of a Jira project.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct ProjectCategory {
     pub description: String,
     pub id: String,
@@ -224,6 +1059,7 @@ pub struct ProjectCategory {
 
 /// The priority of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Priority {
     #[serde(rename = "iconUrl")]
     pub icon_url: String,
@@ -237,6 +1073,7 @@ pub struct Priority {
 
 /// The component of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Component {
     pub description: Option<String>,
     pub id: String,
@@ -249,6 +1086,7 @@ pub struct Component {
 
 /// Users watching a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Watches {
     #[serde(rename = "isWatching")]
     pub is_watching: bool,
@@ -260,20 +1098,50 @@ pub struct Watches {
     pub extra: Value,
 }
 
+/// A search response with issues that only include some of their fields.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct PartialResults {
+    pub issues: Vec<PartialIssue>,
+    pub total: i32,
+}
+
+/// A search response that only includes the `watches` field of each issue.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct WatchesResults {
+    pub issues: Vec<WatchesIssue>,
+}
+
+/// An issue that only includes the `watches` field.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct WatchesIssue {
+    pub key: String,
+    pub fields: WatchesFields,
+}
+
+/// The fields of an issue that only includes the `watches` field.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct WatchesFields {
+    pub watches: Watches,
+}
+
 /// The progress of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Progress {
-    pub progress: i32,
-    pub total: i32,
+    #[serde(deserialize_with = "seconds")]
+    pub progress: i64,
+    #[serde(deserialize_with = "seconds")]
+    pub total: i64,
     #[serde(flatten)]
     pub extra: Value,
 }
 
 /// A comment below a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Comment {
     pub author: User,
-    pub body: String,
+    pub body: Body,
     pub created: DateTime<Utc>,
     pub id: String,
     #[serde(rename = "updateAuthor")]
@@ -288,6 +1156,7 @@ pub struct Comment {
 
 /// A container for all comments below a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Comments {
     pub comments: Vec<Comment>,
     #[serde(rename = "maxResults")]
@@ -299,8 +1168,98 @@ pub struct Comments {
     pub extra: Value,
 }
 
+impl Comments {
+    /// The loaded comments, sorted from the most recently created.
+    #[must_use]
+    pub fn newest_first(&self) -> Vec<&Comment> {
+        let mut comments: Vec<&Comment> = self.comments.iter().collect();
+        comments.sort_by_key(|comment| std::cmp::Reverse(comment.created));
+        comments
+    }
+
+    /// The loaded comments written by this user, compared with `User::same_as`.
+    #[must_use]
+    pub fn by_author(&self, author: &User) -> Vec<&Comment> {
+        self.comments
+            .iter()
+            .filter(|comment| comment.author.same_as(author))
+            .collect()
+    }
+
+    /// The loaded comments created at or after this time.
+    #[must_use]
+    pub fn since(&self, time: DateTime<Utc>) -> Vec<&Comment> {
+        self.comments
+            .iter()
+            .filter(|comment| comment.created >= time)
+            .collect()
+    }
+}
+
+/// An entry of work logged on a Jira issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Worklog {
+    pub author: User,
+    #[serde(rename = "updateAuthor")]
+    pub update_author: Option<User>,
+    pub comment: Option<String>,
+    pub created: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    /// When the logged work started.
+    pub started: DateTime<Utc>,
+    /// The logged time in the readable form, such as `2h 30m`.
+    #[serde(rename = "timeSpent")]
+    pub time_spent: String,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: i64,
+    pub id: String,
+    #[serde(rename = "issueId")]
+    pub issue_id: Option<String>,
+    pub visibility: Option<Visibility>,
+    #[serde(rename = "self")]
+    pub self_link: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A file attached to a Jira issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Attachment {
+    pub id: String,
+    pub filename: String,
+    pub author: User,
+    pub created: DateTime<Utc>,
+    /// The size of the file in bytes.
+    pub size: i64,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    /// The URL that downloads the file. See `JiraInstance::download_attachment`.
+    pub content: String,
+    #[serde(rename = "self")]
+    pub self_link: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A container for the work logged on a Jira issue.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Worklogs {
+    pub worklogs: Vec<Worklog>,
+    #[serde(rename = "maxResults")]
+    pub max_results: i32,
+    #[serde(rename = "startAt")]
+    pub start_at: i32,
+    pub total: i32,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
 /// A link from one Jira issue to another.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct IssueLink {
     pub id: String,
     #[serde(rename = "outwardIssue")]
@@ -317,6 +1276,7 @@ pub struct IssueLink {
 
 /// A Jira issue linked from another one.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct LinkedIssue {
     pub id: String,
     pub key: String,
@@ -327,8 +1287,18 @@ pub struct LinkedIssue {
     pub extra: Value,
 }
 
+impl LinkedIssue {
+    /// The URL of the linked issue in the Jira web UI, derived from its REST `self` link.
+    /// Returns `None` if the link isn't a REST URL.
+    #[must_use]
+    pub fn browse_url(&self) -> Option<String> {
+        browse_url(&self.self_link, &self.key)
+    }
+}
+
 /// The reduced fields of a linked Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct LinkedIssueFields {
     pub issuetype: IssueType,
     pub priority: Option<Priority>,
@@ -340,6 +1310,7 @@ pub struct LinkedIssueFields {
 
 /// The direction of a link to a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct IssueLinkType {
     pub id: String,
     pub inward: String,
@@ -353,6 +1324,7 @@ pub struct IssueLinkType {
 
 /// The votes for a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Votes {
     #[serde(rename = "hasVoted")]
     pub has_voted: bool,
@@ -367,9 +1339,10 @@ pub struct Votes {
 ///
 /// * `xsmall` = 16x16 px
 /// * `small` = 24x24 px
-/// * `medium` = 48x48 px
-/// * `full` = maximum
+/// * `medium` = 32x32 px
+/// * `large` = 48x48 px
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct AvatarUrls {
     #[serde(rename = "16x16")]
     pub xsmall: String,
@@ -378,13 +1351,14 @@ pub struct AvatarUrls {
     #[serde(rename = "32x32")]
     pub medium: String,
     #[serde(rename = "48x48")]
-    pub full: String,
+    pub large: String,
     #[serde(flatten)]
     pub extra: Value,
 }
 
 /// A minimal, reduced representation of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct CondensedIssue {
     pub fields: CondensedFields,
     pub id: String,
@@ -395,8 +1369,50 @@ pub struct CondensedIssue {
     pub extra: Value,
 }
 
+/// An issue with only the fields that the request selected,
+/// as returned by `JiraInstance::issue_with_fields`.
+///
+/// Only the ID, the key, and the link are guaranteed. The `fields` map holds
+/// the requested fields by their ID. A field that the issue doesn't have,
+/// or that the user can't see, is missing from the map.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct PartialIssue {
+    pub id: String,
+    pub key: String,
+    #[serde(rename = "self")]
+    pub self_link: String,
+    #[serde(default)]
+    pub fields: HashMap<String, Value>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+impl PartialIssue {
+    /// The value of the field with this ID, such as `summary` or `status`,
+    /// deserialized into the type of your choice, such as `String` or `Status`.
+    ///
+    /// Returns `Ok(None)` if the field is missing or if its value is `null`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value of the field doesn't match the type.
+    pub fn field<T: DeserializeOwned>(&self, id: &str) -> Result<Option<T>, serde_json::Error> {
+        typed_field(self.fields.get(id))
+    }
+}
+
+/// Deserialize the value of a field, if it's present and not `null`.
+fn typed_field<T: DeserializeOwned>(value: Option<&Value>) -> Result<Option<T>, serde_json::Error> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => T::deserialize(value).map(Some),
+    }
+}
+
 /// A minimal, reduced listing of the fields of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct CondensedFields {
     pub issuetype: IssueType,
     pub priority: Option<Priority>,
@@ -406,8 +1422,37 @@ pub struct CondensedFields {
     pub extra: Value,
 }
 
+impl CondensedIssue {
+    /// The URL of the issue in the Jira web UI, derived from its REST `self` link.
+    /// Returns `None` if the link isn't a REST URL.
+    #[must_use]
+    pub fn browse_url(&self) -> Option<String> {
+        browse_url(&self.self_link, &self.key)
+    }
+}
+
+impl From<&Issue> for CondensedIssue {
+    /// Reduce the issue to the fields of the condensed form, such as for a lightweight reference.
+    fn from(issue: &Issue) -> Self {
+        Self {
+            fields: CondensedFields {
+                issuetype: issue.fields.issuetype.clone(),
+                priority: issue.fields.priority.clone(),
+                status: issue.fields.status.clone(),
+                summary: issue.fields.summary.clone(),
+                extra: Value::Object(serde_json::Map::new()),
+            },
+            id: issue.id.clone(),
+            key: issue.key.clone(),
+            self_link: issue.self_link.clone(),
+            extra: Value::Object(serde_json::Map::new()),
+        }
+    }
+}
+
 /// The visibility of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 pub struct Visibility {
     pub r#type: String,
     pub value: String,
@@ -417,6 +1462,7 @@ pub struct Visibility {
 
 /// The security level of a Jira issue.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
 // TODO: This seems to be a generic container, similar to several other structs.
 // In a future major release, try to consolidate them into one generic struct with:
 // description, id, name.
@@ -430,3 +1476,827 @@ pub struct Security {
     #[serde(flatten)]
     pub extra: Value,
 }
+
+/// A set of changes to the fields of an issue, made at the same time, as a webhook reports it.
+/// Unlike a `History` entry of `Issue::changelog`, it has no author or time;
+/// those are in `WebhookPayload::user` and `WebhookPayload::timestamp`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct WebhookChangelog {
+    pub id: String,
+    pub items: Vec<HistoryItem>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The change history of an issue: the history entries, each with the changes
+/// that a user made at the same time.
+///
+/// From a search with `SearchOptions::changelog`, the history might be incomplete,
+/// if `total` is larger than the number of entries. `JiraInstance::issue_with_changelog`
+/// downloads the complete history, from the oldest entry.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct Changelog {
+    pub histories: Vec<History>,
+    #[serde(rename = "maxResults")]
+    pub max_results: i32,
+    #[serde(rename = "startAt")]
+    pub start_at: i32,
+    pub total: i32,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A set of changes to the fields of an issue, with the user who made them and when.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct History {
+    pub id: String,
+    /// Jira omits the author of changes that no user made, such as by an anonymous
+    /// or a deleted user.
+    pub author: Option<User>,
+    pub created: DateTime<Utc>,
+    pub items: Vec<HistoryItem>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// A page of the changelog endpoint of an issue, on Jira Cloud.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct ChangelogPage {
+    pub values: Vec<History>,
+    #[serde(rename = "isLast", default = "default_true")]
+    pub is_last: bool,
+}
+
+/// A change to a single field of an issue. The raw values, such as IDs,
+/// are in `from` and `to`, and their readable forms in `from_string` and `to_string`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(any(test, feature = "test-util"), derive(Default))]
+pub struct HistoryItem {
+    pub field: String,
+    pub fieldtype: String,
+    #[serde(rename = "fieldId")]
+    pub field_id: Option<String>,
+    pub from: Option<String>,
+    #[serde(rename = "fromString")]
+    pub from_string: Option<String>,
+    pub to: Option<String>,
+    #[serde(rename = "toString")]
+    pub to_string: Option<String>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+/// The web UI URL of the issue with this key, on the host from the REST `self` link,
+/// such as `https://jira.example.com/browse/TEST-1`.
+fn browse_url(self_link: &str, key: &str) -> Option<String> {
+    self_link
+        .find("/rest/")
+        .map(|rest| format!("{}/browse/{key}", &self_link[..rest]))
+}
+
+// The named sizes of avatars in Jira, by their size in pixels.
+const AVATAR_SIZES: [(u32, &str); 7] = [
+    (16, "xsmall"),
+    (24, "small"),
+    (32, "medium"),
+    (48, "large"),
+    (64, "xlarge"),
+    (96, "xxlarge"),
+    (128, "xxxlarge"),
+];
+
+/// Replace the `size` parameter of the avatar URL with the smallest named size
+/// of at least `px` pixels. A URL without the parameter stays unchanged.
+fn with_avatar_size(url: &str, px: u32) -> String {
+    let (base, query) = match url.split_once('?') {
+        Some(parts) => parts,
+        None => return url.to_string(),
+    };
+
+    if !query
+        .split('&')
+        .any(|parameter| parameter.starts_with("size="))
+    {
+        return url.to_string();
+    }
+
+    let size = AVATAR_SIZES
+        .iter()
+        .find(|(pixels, _)| *pixels >= px)
+        .map_or("xxxlarge", |(_, name)| name);
+    let query: Vec<String> = query
+        .split('&')
+        .map(|parameter| {
+            if parameter.starts_with("size=") {
+                format!("size={size}")
+            } else {
+                parameter.to_string()
+            }
+        })
+        .collect();
+
+    format!("{base}?{}", query.join("&"))
+}
+
+/// A number of seconds as Jira returns it: an integer, a floating-point number, or a string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Seconds {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Seconds {
+    /// Convert the value to whole seconds. Fractions of a second are rounded.
+    // The float conversion saturates at the `i64` limits, which are far beyond any estimate.
+    #[allow(clippy::cast_possible_truncation)]
+    fn into_seconds<E: serde::de::Error>(self) -> Result<i64, E> {
+        match self {
+            Self::Integer(seconds) => Ok(seconds),
+            Self::Float(seconds) => Ok(seconds.round() as i64),
+            Self::Text(text) => {
+                let text = text.trim();
+                text.parse::<i64>()
+                    .or_else(|_| text.parse::<f64>().map(|seconds| seconds.round() as i64))
+                    .map_err(|_| E::custom(format!("invalid number of seconds: {text:?}")))
+            }
+        }
+    }
+}
+
+/// Deserialize a time field in seconds that accepts integers, floats, and strings.
+fn seconds<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Seconds::deserialize(deserializer)?.into_seconds()
+}
+
+/// Convert a time tracking field in seconds to a duration.
+/// Negative values and values beyond the range of `Duration` are invalid.
+fn seconds_to_duration(seconds: i64) -> Option<Duration> {
+    if seconds < 0 {
+        return None;
+    }
+    // `Duration::seconds` panics out of range, unlike milliseconds within `i64`.
+    seconds.checked_mul(1000).map(Duration::milliseconds)
+}
+
+/// Deserialize an optional time field in seconds that accepts integers, floats, and strings.
+fn optional_seconds<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<Seconds>::deserialize(deserializer)?
+        .map(Seconds::into_seconds)
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Construct a minimal issue from the default values and check that
+    /// it survives a round trip through JSON.
+    #[test]
+    fn construct_minimal_issue() {
+        let issue = Issue {
+            key: "TEST-1".to_string(),
+            fields: Fields {
+                summary: "A test issue".to_string(),
+                labels: vec!["test".to_string()],
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+
+        let json = serde_json::to_value(&issue).unwrap();
+        let parsed: Issue = serde_json::from_value(json).unwrap();
+
+        assert_eq!(parsed.key, "TEST-1");
+        assert_eq!(parsed.fields.summary, "A test issue");
+        assert_eq!(parsed.fields.labels, vec!["test"]);
+        assert!(parsed.fields.assignee.is_none());
+    }
+
+    /// Classify versions in each release state.
+    #[test]
+    fn version_status() {
+        let today = NaiveDate::from_ymd_opt(2023, 6, 15).unwrap();
+        let version = |released: bool, archived: bool, release_date: Option<NaiveDate>| Version {
+            archived,
+            released,
+            release_date,
+            ..Version::default()
+        };
+        let past = NaiveDate::from_ymd_opt(2023, 6, 1);
+        let future = NaiveDate::from_ymd_opt(2023, 7, 1);
+
+        assert_eq!(
+            version(false, false, future).status(today),
+            VersionStatus::Unreleased
+        );
+        assert_eq!(
+            version(false, false, None).status(today),
+            VersionStatus::Unreleased
+        );
+        assert_eq!(
+            version(true, false, past).status(today),
+            VersionStatus::Released
+        );
+        assert_eq!(
+            version(true, true, past).status(today),
+            VersionStatus::Archived
+        );
+        assert_eq!(
+            version(false, false, past).status(today),
+            VersionStatus::Overdue
+        );
+
+        assert!(version(false, false, past).is_overdue(today));
+        assert!(!version(true, false, past).is_overdue(today));
+        assert!(!version(false, false, future).is_overdue(today));
+    }
+
+    /// Find the latest comment in a thread with out-of-order creation times.
+    #[test]
+    fn latest_comment() {
+        let comment = |id: &str, day: u32| Comment {
+            id: id.to_string(),
+            created: Utc.with_ymd_and_hms(2023, 1, day, 12, 0, 0).unwrap(),
+            ..Comment::default()
+        };
+        let mut fields = Fields::default();
+        assert!(fields.latest_comment().is_none());
+
+        fields.comment = Some(Comments {
+            comments: vec![comment("1", 10), comment("3", 14), comment("2", 12)],
+            ..Comments::default()
+        });
+        assert_eq!(fields.latest_comment().unwrap().id, "3");
+    }
+
+    /// Measure the lead time of a resolved issue, and none for an unresolved issue.
+    #[test]
+    fn resolution_lead_time() {
+        let mut fields = Fields {
+            created: Utc.with_ymd_and_hms(2023, 1, 10, 9, 0, 0).unwrap(),
+            ..Fields::default()
+        };
+        assert_eq!(fields.resolution_sla(), None);
+
+        fields.resolutiondate = Some(Utc.with_ymd_and_hms(2023, 1, 12, 15, 30, 0).unwrap());
+        assert_eq!(
+            fields.resolution_sla(),
+            Some(Duration::days(2) + Duration::hours(6) + Duration::minutes(30))
+        );
+    }
+
+    /// Find the unfinished issues that target a version.
+    #[test]
+    fn release_blockers() {
+        let issue = |key: &str, version: &str, category: &str| Issue {
+            key: key.to_string(),
+            fields: Fields {
+                fix_versions: vec![Version {
+                    name: version.to_string(),
+                    ..Version::default()
+                }],
+                status: Status {
+                    status_category: StatusCategory {
+                        key: category.to_string(),
+                        ..StatusCategory::default()
+                    },
+                    ..Status::default()
+                },
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let issues = vec![
+            issue("TEST-1", "1.0", "indeterminate"),
+            issue("TEST-2", "1.0", "done"),
+            issue("TEST-3", "2.0", "new"),
+            issue("TEST-4", "1.0", "new"),
+        ];
+
+        let blockers: Vec<&str> = unreleased_blockers(&issues, "1.0")
+            .iter()
+            .map(|issue| issue.key.as_str())
+            .collect();
+        assert_eq!(blockers, ["TEST-1", "TEST-4"]);
+    }
+
+    /// Group issues by their `team:` labels.
+    #[test]
+    fn label_groups() {
+        let issue = |key: &str, labels: &[&str]| Issue {
+            key: key.to_string(),
+            fields: Fields {
+                labels: labels.iter().map(ToString::to_string).collect(),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let issues = vec![
+            issue("TEST-1", &["team:core", "backend"]),
+            issue("TEST-2", &["team:ui"]),
+            issue("TEST-3", &["team:core", "team:ui"]),
+            issue("TEST-4", &["frontend"]),
+        ];
+
+        let groups = group_by_label(&issues, "team:");
+        let keys = |group: &str| -> Vec<&str> {
+            groups[group]
+                .iter()
+                .map(|issue| issue.key.as_str())
+                .collect()
+        };
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(keys("core"), ["TEST-1", "TEST-3"]);
+        assert_eq!(keys("ui"), ["TEST-2", "TEST-3"]);
+    }
+
+    /// Place issues in board columns by status, with the category as a fallback.
+    #[test]
+    fn board_columns() {
+        let issue = |status: &str, category: &str| Issue {
+            fields: Fields {
+                status: Status {
+                    name: status.to_string(),
+                    status_category: StatusCategory {
+                        name: category.to_string(),
+                        ..StatusCategory::default()
+                    },
+                    ..Status::default()
+                },
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let mapping: HashMap<String, String> = [("Code Review", "Review"), ("QA", "Review")]
+            .iter()
+            .map(|&(status, column)| (status.to_string(), column.to_string()))
+            .collect();
+
+        assert_eq!(
+            column_of(&issue("QA", "In Progress"), &mapping),
+            Some("Review")
+        );
+        assert_eq!(
+            column_of(&issue("Coding", "In Progress"), &mapping),
+            Some("In Progress")
+        );
+        assert_eq!(column_of(&issue("Coding", ""), &mapping), None);
+    }
+
+    /// Count labels across issues, with the most frequent first.
+    #[test]
+    fn label_counts() {
+        let issue = |labels: &[&str]| Issue {
+            fields: Fields {
+                labels: labels.iter().map(ToString::to_string).collect(),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let issues = vec![
+            issue(&["backend", "urgent"]),
+            issue(&["backend", "backend"]),
+            issue(&["frontend", "backend", "urgent"]),
+            issue(&["docs"]),
+        ];
+
+        assert_eq!(
+            label_frequencies(&issues),
+            [
+                ("backend".to_string(), 3),
+                ("urgent".to_string(), 2),
+                ("docs".to_string(), 1),
+                ("frontend".to_string(), 1),
+            ]
+        );
+        assert!(label_frequencies(&[]).is_empty());
+    }
+
+    /// Show the time of the last update in a named time zone,
+    /// with the offset that applies on each side of the daylight saving time change.
+    #[test]
+    fn updated_in_time_zone() {
+        let updated_in_prague = |utc: DateTime<Utc>| {
+            let fields = Fields {
+                updated: utc,
+                ..Fields::default()
+            };
+            let local = fields.updated_in_tz(&chrono_tz::Europe::Prague);
+            assert_eq!(local, fields.updated);
+            local.to_rfc3339()
+        };
+
+        // The clocks moved forward at 01:00 UTC on March 26, 2023.
+        assert_eq!(
+            updated_in_prague(Utc.with_ymd_and_hms(2023, 3, 26, 0, 30, 0).unwrap()),
+            "2023-03-26T01:30:00+01:00"
+        );
+        assert_eq!(
+            updated_in_prague(Utc.with_ymd_and_hms(2023, 3, 26, 1, 30, 0).unwrap()),
+            "2023-03-26T03:30:00+02:00"
+        );
+        // And back at 01:00 UTC on October 29, 2023, so 02:30 local time happened twice.
+        assert_eq!(
+            updated_in_prague(Utc.with_ymd_and_hms(2023, 10, 29, 0, 30, 0).unwrap()),
+            "2023-10-29T02:30:00+02:00"
+        );
+        assert_eq!(
+            updated_in_prague(Utc.with_ymd_and_hms(2023, 10, 29, 1, 30, 0).unwrap()),
+            "2023-10-29T02:30:00+01:00"
+        );
+    }
+
+    /// Detect issues without a component or a fix version.
+    #[test]
+    fn triage_checks() {
+        let mut fields = Fields::default();
+        assert!(fields.has_no_component());
+        assert!(fields.has_no_fix_version());
+
+        fields.components.push(Component::default());
+        fields.fix_versions.push(Version::default());
+        assert!(!fields.has_no_component());
+        assert!(!fields.has_no_fix_version());
+    }
+
+    /// Recognize the impediment flag in a multi-select field.
+    #[test]
+    fn impediment_flag() {
+        let fields = |flag: Value| Fields {
+            extra: serde_json::json!({ "customfield_10021": flag }),
+            ..Fields::default()
+        };
+        let flagged = fields(serde_json::json!([
+            { "self": "https://jira.example.com/rest/api/2/customFieldOption/10019", "value": "Impediment", "id": "10019" }
+        ]));
+
+        assert!(flagged.is_flagged("customfield_10021"));
+        assert!(!flagged.is_flagged("customfield_10022"));
+        assert!(!fields(Value::Null).is_flagged("customfield_10021"));
+        assert!(!fields(serde_json::json!([])).is_flagged("customfield_10021"));
+    }
+
+    /// Prefer the rendered description, and fall back to the raw one.
+    #[test]
+    fn display_description() {
+        let mut issue = Issue {
+            fields: Fields {
+                description: Some(Body::Text("*Bold* text".to_string())),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        assert_eq!(issue.display_description().as_deref(), Some("*Bold* text"));
+
+        issue.rendered_fields = Some(RenderedFields {
+            description: Some("<p><b>Bold</b> text</p>".to_string()),
+            ..RenderedFields::default()
+        });
+        assert_eq!(
+            issue.display_description().as_deref(),
+            Some("<p><b>Bold</b> text</p>")
+        );
+    }
+
+    /// Derive the browse URLs of related issues from their REST links.
+    #[test]
+    fn browse_urls() {
+        let linked = LinkedIssue {
+            key: "TEST-2".to_string(),
+            self_link: "https://jira.example.com/rest/api/2/issue/10002".to_string(),
+            ..LinkedIssue::default()
+        };
+        assert_eq!(
+            linked.browse_url().as_deref(),
+            Some("https://jira.example.com/browse/TEST-2")
+        );
+
+        // An instance on a context path keeps the path.
+        let condensed = CondensedIssue {
+            key: "TEST-3".to_string(),
+            self_link: "https://example.com/jira/rest/api/2/issue/10003".to_string(),
+            ..CondensedIssue::default()
+        };
+        assert_eq!(
+            condensed.browse_url().as_deref(),
+            Some("https://example.com/jira/browse/TEST-3")
+        );
+
+        let unknown = CondensedIssue::default();
+        assert_eq!(unknown.browse_url(), None);
+    }
+
+    /// Merge two overlapping result sets.
+    #[test]
+    fn merge_overlapping_results() {
+        let results = |keys: &[&str]| JqlResults {
+            issues: keys
+                .iter()
+                .map(|key| Issue {
+                    key: (*key).to_string(),
+                    ..Issue::default()
+                })
+                .collect(),
+            ..JqlResults::default()
+        };
+
+        let merged = merge_results(vec![
+            results(&["TEST-1", "TEST-2"]),
+            results(&["TEST-3", "TEST-1", "TEST-4"]),
+        ]);
+        let keys: Vec<&str> = merged.iter().map(|issue| issue.key.as_str()).collect();
+
+        assert_eq!(keys, ["TEST-1", "TEST-2", "TEST-3", "TEST-4"]);
+    }
+
+    /// Report the work ratio as a percentage, and none without an estimate.
+    #[test]
+    fn work_ratio() {
+        let mut fields = Fields {
+            workratio: -1,
+            ..Fields::default()
+        };
+        assert_eq!(fields.work_ratio(), None);
+
+        fields.workratio = 50;
+        assert_eq!(fields.work_ratio(), Some(50));
+    }
+
+    /// Count wide characters as two columns and zero-width characters as none.
+    #[test]
+    fn summary_width() {
+        let width = |summary: &str| {
+            Fields {
+                summary: summary.to_string(),
+                ..Fields::default()
+            }
+            .summary_display_width()
+        };
+
+        // The same number of characters, but the CJK summary is twice as wide.
+        assert_eq!(width("Crash"), 5);
+        assert_eq!(width("起動時の停"), 10);
+        assert_eq!(width("Fix 起動"), 8);
+        // A combining accent takes no column, and an emoji ZWJ sequence is a single glyph.
+        assert_eq!(width("Cafe\u{301}"), 4);
+        assert_eq!(width("\u{1F468}\u{200D}\u{1F4BB}"), 2);
+        assert_eq!(width("🐛 bug"), 6);
+    }
+
+    /// Pick the progress of the issue or the aggregate with the subtasks,
+    /// and report none without a total.
+    #[test]
+    fn completion_ratio() {
+        let progress = |progress: i64, total: i64| Progress {
+            progress,
+            total,
+            ..Progress::default()
+        };
+        let mut fields = Fields {
+            progress: progress(3600, 14400),
+            aggregateprogress: progress(7200, 57600),
+            ..Fields::default()
+        };
+
+        assert_eq!(fields.completion_ratio(false), Some(0.25));
+        assert_eq!(fields.completion_ratio(true), Some(0.125));
+
+        fields.progress = progress(0, 0);
+        assert_eq!(fields.completion_ratio(false), None);
+        assert_eq!(fields.completion_ratio(true), Some(0.125));
+    }
+
+    /// Convert the time tracking seconds to durations, and reject negative values.
+    #[test]
+    fn time_tracking_durations() {
+        let fields = Fields {
+            timeestimate: Some(0),
+            timeoriginalestimate: Some(28_800),
+            timespent: Some(-60),
+            aggregatetimespent: Some(i64::MAX),
+            aggregatetimeestimate: Some(5400),
+            ..Fields::default()
+        };
+
+        assert_eq!(fields.time_estimate_duration(), Some(Duration::zero()));
+        assert_eq!(
+            fields.time_original_estimate_duration(),
+            Some(Duration::hours(8))
+        );
+        assert_eq!(
+            fields.aggregate_time_estimate_duration(),
+            Some(Duration::minutes(90))
+        );
+        assert_eq!(fields.time_spent_duration(), None);
+        assert_eq!(fields.aggregate_time_spent_duration(), None);
+        assert_eq!(fields.aggregate_time_original_estimate_duration(), None);
+    }
+
+    /// Sort and filter the comments of a thread.
+    #[test]
+    fn comment_helpers() {
+        let user = |key: &str| User {
+            key: Some(key.to_string()),
+            ..User::default()
+        };
+        let time = |hour: u32| Utc.with_ymd_and_hms(2023, 1, 10, hour, 0, 0).unwrap();
+        let comment = |id: &str, author: &str, hour: u32| Comment {
+            author: user(author),
+            created: time(hour),
+            id: id.to_string(),
+            ..Comment::default()
+        };
+        let comments = Comments {
+            comments: vec![
+                comment("1", "alice", 9),
+                comment("2", "bob", 12),
+                comment("3", "alice", 10),
+            ],
+            ..Comments::default()
+        };
+        let ids = |comments: Vec<&Comment>| -> Vec<String> {
+            comments.iter().map(|comment| comment.id.clone()).collect()
+        };
+
+        assert_eq!(ids(comments.newest_first()), vec!["2", "3", "1"]);
+        assert_eq!(ids(comments.by_author(&user("alice"))), vec!["1", "3"]);
+        assert_eq!(ids(comments.since(time(10))), vec!["2", "3"]);
+    }
+
+    /// Compare users across the combinations of identity fields.
+    #[test]
+    fn same_user() {
+        let user = |account_id: Option<&str>, key: Option<&str>, name: Option<&str>| User {
+            account_id: account_id.map(String::from),
+            key: key.map(String::from),
+            name: name.map(String::from),
+            ..User::default()
+        };
+
+        // Cloud users with account IDs.
+        assert!(user(Some("5b10a"), None, None).same_as(&user(Some("5b10a"), None, None)));
+        assert!(!user(Some("5b10a"), None, None).same_as(&user(Some("5b10b"), None, None)));
+        // The account ID takes precedence over a matching key.
+        assert!(!user(Some("5b10a"), Some("k"), None).same_as(&user(
+            Some("5b10b"),
+            Some("k"),
+            None
+        )));
+        // Server users with keys and names.
+        assert!(user(None, Some("JIRAUSER1"), Some("a")).same_as(&user(
+            None,
+            Some("JIRAUSER1"),
+            Some("b")
+        )));
+        assert!(!user(None, Some("JIRAUSER1"), Some("a")).same_as(&user(
+            None,
+            Some("JIRAUSER2"),
+            Some("a")
+        )));
+        // Only one user has an account ID, so fall back to the key.
+        assert!(user(Some("5b10a"), Some("k"), None).same_as(&user(None, Some("k"), None)));
+        // Only the names are comparable.
+        assert!(user(None, Some("k"), Some("jdoe")).same_as(&user(None, None, Some("jdoe"))));
+        // No common identifier.
+        assert!(!user(Some("5b10a"), None, None).same_as(&user(None, Some("k"), Some("jdoe"))));
+        assert!(!user(None, None, None).same_as(&user(None, None, None)));
+    }
+
+    /// Resize issue type icons, and keep icons without a size as they are.
+    #[test]
+    fn icon_sizes() {
+        let issue_type = |url: &str| IssueType {
+            icon_url: url.to_string(),
+            ..IssueType::default()
+        };
+        let avatar = issue_type(
+            "https://jira.example.com/secure/viewavatar?size=xsmall&avatarId=10318&avatarType=issuetype",
+        );
+
+        assert_eq!(
+            avatar.icon_url_sized(48),
+            "https://jira.example.com/secure/viewavatar?size=large&avatarId=10318&avatarType=issuetype"
+        );
+        // Pick the next larger size, and cap the size at the largest one.
+        assert!(avatar.icon_url_sized(40).contains("size=large&"));
+        assert!(avatar.icon_url_sized(16).contains("size=xsmall&"));
+        assert!(avatar.icon_url_sized(512).contains("size=xxxlarge&"));
+
+        let cloud = issue_type(
+            "https://example.atlassian.net/rest/api/2/universal_avatar/view/type/issuetype/avatar/10303?size=medium",
+        );
+        assert_eq!(
+            cloud.icon_url_sized(64),
+            "https://example.atlassian.net/rest/api/2/universal_avatar/view/type/issuetype/avatar/10303?size=xlarge"
+        );
+
+        let static_icon = "https://jira.example.com/images/icons/issuetypes/bug.png";
+        assert_eq!(issue_type(static_icon).icon_url_sized(48), static_icon);
+        let no_size = "https://jira.example.com/secure/viewavatar?avatarId=10318";
+        assert_eq!(issue_type(no_size).icon_url_sized(48), no_size);
+    }
+
+    /// Extract the email domain, and handle hidden or malformed addresses.
+    #[test]
+    fn email_domain() {
+        let user = |email: Option<&str>| User {
+            email_address: email.map(String::from),
+            ..User::default()
+        };
+
+        assert_eq!(
+            user(Some("jdoe@example.com")).email_domain(),
+            Some("example.com")
+        );
+        assert_eq!(
+            user(Some("\"j@doe\"@mail.example.org")).email_domain(),
+            Some("mail.example.org")
+        );
+        // Jira hides the address, or sends an empty one.
+        assert_eq!(user(None).email_domain(), None);
+        assert_eq!(user(Some("")).email_domain(), None);
+        assert_eq!(user(Some("jdoe@")).email_domain(), None);
+    }
+
+    /// Map the colors of the three standard status categories, and an unknown color.
+    #[test]
+    fn status_category_colors() {
+        let category = |color: &str| StatusCategory {
+            color_name: color.to_string(),
+            ..StatusCategory::default()
+        };
+
+        assert_eq!(category("blue-gray").color_hex(), "#42526E");
+        assert_eq!(category("yellow").color_hex(), "#FFAB00");
+        assert_eq!(category("green").color_hex(), "#36B37E");
+        assert_eq!(category("ultraviolet").color_hex(), "#7A869A");
+    }
+
+    /// Read the pagination metadata of search results, and keep the rest in `extra`.
+    #[test]
+    fn search_metadata() {
+        let results: JqlResults = serde_json::from_value(serde_json::json!({
+            "expand": "schema,names",
+            "startAt": 50,
+            "maxResults": 50,
+            "total": 120,
+            "issues": [],
+        }))
+        .unwrap();
+
+        assert_eq!(results.start_at, 50);
+        assert_eq!(results.max_results, 50);
+        assert_eq!(results.total, 120);
+        assert_eq!(
+            results.extra,
+            serde_json::json!({ "expand": "schema,names" })
+        );
+    }
+
+    /// Report the changed fields of an issue, including a custom field that appeared.
+    #[test]
+    fn issue_diff() {
+        let before = Issue {
+            fields: Fields {
+                summary: "Old".to_string(),
+                labels: vec!["backend".to_string()],
+                extra: serde_json::json!({}),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let mut after = before.clone();
+        after.fields.summary = "New".to_string();
+        after.fields.extra = serde_json::json!({ "customfield_10020": 5 });
+
+        assert!(before.diff(&before).is_empty());
+        assert_eq!(
+            before.diff(&after),
+            [
+                FieldChange {
+                    field: "customfield_10020".to_string(),
+                    from: Value::Null,
+                    to: serde_json::json!(5),
+                },
+                FieldChange {
+                    field: "summary".to_string(),
+                    from: serde_json::json!("Old"),
+                    to: serde_json::json!("New"),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Serialize date-times in the input format of Jira: `yyyy-MM-dd'T'HH:mm:ss.SSSZ`,
+//! such as `2023-01-10T09:00:00.000+0000`, with milliseconds and no colon in the offset.
+//!
+//! Jira rejects the default RFC 3339 serialization of `chrono`, which has a colon
+//! in the offset. Use the functions on the date-time fields of your own request bodies:
+//!
+//! ```
+//! use chrono::{DateTime, TimeZone, Utc};
+//! use serde::Serialize;
+//!
+//! #[derive(Serialize)]
+//! struct Worklog {
+//!     #[serde(serialize_with = "jira_query::jira_datetime::serialize")]
+//!     started: DateTime<Utc>,
+//! }
+//!
+//! let worklog = Worklog {
+//!     started: Utc.with_ymd_and_hms(2023, 1, 10, 9, 0, 0).unwrap(),
+//! };
+//! assert_eq!(
+//!     serde_json::to_string(&worklog).unwrap(),
+//!     r#"{"started":"2023-01-10T09:00:00.000+0000"}"#
+//! );
+//! ```
+
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeZone};
+use serde::Serializer;
+
+// The date-time input format of Jira, with milliseconds and an offset without a colon.
+const JIRA_DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%z";
+
+/// Format the date-time in the input format of Jira.
+#[must_use]
+pub fn format<Tz>(datetime: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    datetime.format(JIRA_DATETIME_FORMAT).to_string()
+}
+
+/// Serialize the date-time as a string in the input format of Jira.
+/// Use it with `#[serde(serialize_with = "jira_query::jira_datetime::serialize")]`.
+///
+/// # Errors
+///
+/// Fails if the serializer fails to write the string.
+pub fn serialize<S, Tz>(datetime: &DateTime<Tz>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    serializer.serialize_str(&format(datetime))
+}
+
+/// Serialize an optional date-time in the input format of Jira, or `None` as `null`.
+/// Use it with `#[serde(serialize_with = "jira_query::jira_datetime::serialize_option")]`.
+///
+/// # Errors
+///
+/// Fails if the serializer fails to write the value.
+pub fn serialize_option<S, Tz>(
+    datetime: &Option<DateTime<Tz>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    match datetime {
+        Some(datetime) => serialize(datetime, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+    use serde::Serialize;
+
+    /// Format date-times with milliseconds and an offset without a colon.
+    #[test]
+    fn jira_datetime_format() {
+        let utc = Utc.with_ymd_and_hms(2023, 1, 10, 9, 5, 7).unwrap();
+        assert_eq!(format(&utc), "2023-01-10T09:05:07.000+0000");
+
+        let prague = FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2023, 1, 10, 10, 5, 7)
+            .unwrap();
+        assert_eq!(format(&prague), "2023-01-10T10:05:07.000+0100");
+    }
+
+    /// Serialize required and optional date-time fields.
+    #[test]
+    fn serialize_fields() {
+        #[derive(Serialize)]
+        struct Dates {
+            #[serde(serialize_with = "serialize")]
+            started: DateTime<Utc>,
+            #[serde(serialize_with = "serialize_option")]
+            due: Option<DateTime<FixedOffset>>,
+            #[serde(serialize_with = "serialize_option")]
+            ended: Option<DateTime<Utc>>,
+        }
+
+        let dates = Dates {
+            started: Utc.with_ymd_and_hms(2023, 1, 10, 9, 5, 7).unwrap(),
+            due: FixedOffset::west_opt(5 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2023, 2, 1, 17, 0, 0)
+                .single(),
+            ended: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&dates).unwrap(),
+            serde_json::json!({
+                "started": "2023-01-10T09:05:07.000+0000",
+                "due": "2023-02-01T17:00:00.000-0500",
+                "ended": null,
+            })
+        );
+    }
+}
@@ -0,0 +1,281 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Helpers to compose JQL queries safely from arbitrary values.
+
+use chrono::NaiveDate;
+
+use crate::issue_model::Issue;
+
+// The words that JQL reserves. An unquoted reserved word breaks the query,
+// even if it's meant as a value, such as a label named `AND`.
+// See <https://support.atlassian.com/jira-software-cloud/docs/jql-reserved-characters-and-words/>.
+#[rustfmt::skip]
+const RESERVED_WORDS: &[&str] = &[
+    "a", "abort", "access", "add", "after", "alias", "all", "alter", "an", "and", "any", "are",
+    "as", "asc", "audit", "avg", "before", "begin", "between", "boolean", "break", "by", "byte",
+    "catch", "cf", "char", "character", "check", "checkpoint", "collate", "collation", "column",
+    "commit", "connect", "continue", "count", "create", "current", "date", "decimal", "declare",
+    "decrement", "default", "defaults", "define", "delete", "delimiter", "desc", "difference",
+    "distinct", "divide", "do", "double", "drop", "else", "empty", "encoding", "end", "equals",
+    "escape", "exclusive", "exec", "execute", "exists", "explain", "false", "fetch", "file",
+    "field", "first", "float", "for", "from", "function", "go", "goto", "grant", "greater",
+    "group", "having", "identified", "if", "immediate", "in", "increment", "index", "initial",
+    "inner", "inout", "input", "insert", "int", "integer", "intersect", "intersection", "into",
+    "is", "isempty", "isnull", "join", "last", "left", "less", "like", "limit", "lock", "long",
+    "max", "min", "minus", "mode", "modify", "modulo", "more", "multiply", "next", "noaudit",
+    "not", "notin", "nowait", "null", "number", "object", "of", "on", "option", "or", "order",
+    "outer", "output", "power", "previous", "prior", "privileges", "public", "raise", "raw",
+    "remainder", "rename", "resource", "return", "returns", "revoke", "right", "row", "rowid",
+    "rownum", "rows", "select", "session", "set", "share", "size", "sqrt", "start", "strict",
+    "string", "subtract", "sum", "synonym", "table", "then", "to", "trans", "transaction",
+    "trigger", "true", "uid", "union", "unique", "update", "user", "validate", "values", "view",
+    "when", "whenever", "where", "while", "with",
+];
+
+/// Format a field name or a value for a JQL query, such as a project key or a label.
+///
+/// The text stays unquoted if JQL accepts it as is. Otherwise, it's enclosed
+/// in double quotes, with any quotes and backslashes inside escaped. JQL requires quotes
+/// around reserved words, such as `AND`, around text that starts with a digit,
+/// and around text with spaces or special characters.
+///
+/// ```
+/// use jira_query::quote_jql;
+///
+/// let query = format!("project = {} AND labels = {}", quote_jql("TEST"), quote_jql("AND"));
+/// assert_eq!(query, r#"project = TEST AND labels = "AND""#);
+/// ```
+#[must_use]
+pub fn quote_jql(text: &str) -> String {
+    if needs_quotes(text) {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Format a JQL query that matches the issues by their keys, such as to download them again:
+/// `issuekey in (TEST-1, TEST-2)`.
+///
+/// The query is part of the request URL, which servers and proxies limit in length.
+/// For a long list of issues, query them in chunks, such as `issues.chunks(100)`,
+/// and combine the results. Jira rejects the query if the list is empty.
+#[must_use]
+pub fn keys_jql(issues: &[Issue]) -> String {
+    let keys: Vec<String> = issues.iter().map(|issue| quote_jql(&issue.key)).collect();
+    format!("issuekey in ({})", keys.join(", "))
+}
+
+/// The direction of a JQL `ORDER BY` field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    /// The JQL keyword of the direction.
+    const fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// A builder of common JQL queries, which quotes every value with `quote_jql`,
+/// so that values from user input can't alter the query.
+///
+/// The conditions combine with `AND`, in the order that you add them.
+///
+/// ```
+/// use jira_query::{JqlBuilder, Order};
+///
+/// let query = JqlBuilder::new()
+///     .project("ABC")
+///     .status("In Progress")
+///     .assignee_current_user()
+///     .order_by("created", Order::Desc)
+///     .build();
+/// assert_eq!(
+///     query,
+///     r#"project = ABC AND status = "In Progress" AND assignee = currentUser() ORDER BY created DESC"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct JqlBuilder {
+    conditions: Vec<String>,
+    ordering: Vec<String>,
+}
+
+impl JqlBuilder {
+    /// Create a builder of an empty query, which matches all issues.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match the issues in the project with this key or name.
+    #[must_use]
+    pub fn project(self, project: &str) -> Self {
+        self.equals("project", project)
+    }
+
+    /// Match the issues in the status with this name.
+    #[must_use]
+    pub fn status(self, status: &str) -> Self {
+        self.equals("status", status)
+    }
+
+    /// Match the issues assigned to the user with this name or account ID.
+    #[must_use]
+    pub fn assignee(self, user: &str) -> Self {
+        self.equals("assignee", user)
+    }
+
+    /// Match the issues assigned to the user that runs the query.
+    #[must_use]
+    pub fn assignee_current_user(mut self) -> Self {
+        self.conditions.push("assignee = currentUser()".to_string());
+        self
+    }
+
+    /// Match the issues with this label.
+    #[must_use]
+    pub fn label(self, label: &str) -> Self {
+        self.equals("labels", label)
+    }
+
+    /// Match the issues updated on this date or later.
+    #[must_use]
+    pub fn updated_since(mut self, date: NaiveDate) -> Self {
+        self.conditions
+            .push(format!("updated >= \"{}\"", date.format("%Y-%m-%d")));
+        self
+    }
+
+    /// Sort the issues by the field. Further calls add fields that sort the issues
+    /// that are equal in the previous fields.
+    #[must_use]
+    pub fn order_by(mut self, field: &str, order: Order) -> Self {
+        self.ordering
+            .push(format!("{} {}", quote_jql(field), order.keyword()));
+        self
+    }
+
+    /// Format the JQL query.
+    #[must_use]
+    pub fn build(&self) -> String {
+        let mut query = self.conditions.join(" AND ");
+
+        if !self.ordering.is_empty() {
+            if !query.is_empty() {
+                query.push(' ');
+            }
+            query.push_str("ORDER BY ");
+            query.push_str(&self.ordering.join(", "));
+        }
+
+        query
+    }
+
+    /// Add a condition that the field equals the value.
+    fn equals(mut self, field: &str, value: &str) -> Self {
+        self.conditions
+            .push(format!("{field} = {}", quote_jql(value)));
+        self
+    }
+}
+
+/// Check if JQL would misinterpret the text without quotes.
+fn needs_quotes(text: &str) -> bool {
+    let starts_with_letter = text
+        .chars()
+        .next()
+        .map_or(false, |first| first.is_alphabetic() || first == '_');
+    let plain_characters = text
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    let reserved = RESERVED_WORDS.contains(&text.to_lowercase().as_str());
+
+    !starts_with_letter || !plain_characters || reserved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quote reserved words, leading digits, and special characters, but not plain text.
+    #[test]
+    fn quote_values() {
+        assert_eq!(quote_jql("TEST"), "TEST");
+        assert_eq!(quote_jql("TEST-1"), "TEST-1");
+        assert_eq!(quote_jql("team_core"), "team_core");
+
+        assert_eq!(quote_jql("AND"), "\"AND\"");
+        assert_eq!(quote_jql("order"), "\"order\"");
+        assert_eq!(quote_jql("2023-release"), "\"2023-release\"");
+        assert_eq!(quote_jql("team:core"), "\"team:core\"");
+        assert_eq!(quote_jql("two words"), "\"two words\"");
+        assert_eq!(quote_jql(""), "\"\"");
+        assert_eq!(quote_jql(r#"say "hi"\"#), r#""say \"hi\"\\""#);
+    }
+
+    /// Combine conditions and ordering, with the values quoted.
+    #[test]
+    fn build_queries() {
+        let query = JqlBuilder::new()
+            .project("ABC")
+            .label(r#"foo"bar"#)
+            .assignee("jdoe")
+            .updated_since(NaiveDate::from_ymd_opt(2023, 1, 10).unwrap())
+            .order_by("priority", Order::Desc)
+            .order_by("updated", Order::Asc)
+            .build();
+
+        assert_eq!(
+            query,
+            r#"project = ABC AND labels = "foo\"bar" AND assignee = jdoe AND updated >= "2023-01-10" ORDER BY priority DESC, updated ASC"#
+        );
+
+        // A value can't end the quotes early and add its own conditions.
+        assert_eq!(
+            JqlBuilder::new()
+                .status(r#"Open" OR project = SECRET OR status = "Open"#)
+                .build(),
+            r#"status = "Open\" OR project = SECRET OR status = \"Open""#
+        );
+        assert_eq!(JqlBuilder::new().build(), "");
+        assert_eq!(
+            JqlBuilder::new().order_by("created", Order::Asc).build(),
+            "ORDER BY created ASC"
+        );
+    }
+
+    /// Match a list of issues by their keys.
+    #[test]
+    fn issue_keys() {
+        let issue = |key: &str| Issue {
+            key: key.to_string(),
+            ..Issue::default()
+        };
+        let issues = vec![issue("TEST-1"), issue("TEST-2"), issue("OTHER-10")];
+
+        assert_eq!(keys_jql(&issues), "issuekey in (TEST-1, TEST-2, OTHER-10)");
+        assert_eq!(keys_jql(&issues[..1]), "issuekey in (TEST-1)");
+    }
+}
@@ -26,16 +26,43 @@ limitations under the License.
 #![forbid(unsafe_code)]
 
 mod access;
+mod adf;
+mod agile;
+mod cassette;
+mod cycle_time;
+mod development;
 mod errors;
+mod export;
+mod graph;
 mod issue_model;
+pub mod jira_datetime;
+mod jql;
+mod new_issue;
+mod watch;
+mod webhook;
+mod write;
 
-pub use access::{Auth, JiraInstance, Pagination};
+pub use access::{Auth, GroupField, JiraInstance, Pagination, SearchOptions, ValidationLevel};
+pub use adf::{AdfBuilder, AdfDocument, AdfInline, AdfMark, AdfNode, Body};
+pub use agile::Sprint;
+pub use cassette::{RecordingTransport, ReplayTransport};
+pub use cycle_time::CycleTime;
+pub use development::{DevBranch, DevCommit, DevPullRequest, DevRepository, DevelopmentInfo};
 pub use errors::JiraQueryError;
+pub use export::IssueExport;
+pub use graph::links_to_dot;
 pub use issue_model::{
-    AvatarUrls, Comment, Comments, Component, CondensedFields, CondensedIssue, Fields, Issue,
-    IssueLink, IssueLinkType, IssueType, LinkedIssue, LinkedIssueFields, Priority, Progress,
-    Project, ProjectCategory, Resolution, Status, StatusCategory, User, Version, Visibility, Votes,
-    Watches,
+    column_of, group_by_label, label_frequencies, merge_results, unreleased_blockers, Attachment,
+    AvatarUrls, Changelog, Comment, Comments, Component, CondensedFields, CondensedIssue,
+    CreateFieldMeta, FieldChange, FieldMeta, FieldSchema, Fields, FilterColumn, History,
+    HistoryItem, Issue, IssueLink, IssueLinkType, IssueType, JqlResults, LinkedIssue,
+    LinkedIssueFields, PartialIssue, Priority, Progress, Project, ProjectCategory, RenderedFields,
+    RequestType, Resolution, Status, StatusCategory, Transition, User, Version, VersionStatus,
+    Visibility, Votes, Watches, WebhookChangelog, Worklog, Worklogs,
 };
+pub use jql::{keys_jql, quote_jql, JqlBuilder, Order};
+pub use new_issue::{CreatedIssue, FieldProblem, NewIssue};
+pub use webhook::{WebhookEvent, WebhookPayload};
+pub use write::{assign_round_robin, label_operations, LabelOperation, NewWorklog};
 // Re-export JSON Value because it's an integral part of the issue model.
 pub use serde_json::Value;
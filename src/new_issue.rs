@@ -0,0 +1,517 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The fields of an issue that doesn't exist yet, ready to be created in Jira.
+
+use std::fmt::Display;
+
+use chrono::{DateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::issue_model::{CreateFieldMeta, Issue, IssueType};
+use crate::jira_datetime;
+
+/// A new Jira issue to create. Construct it with `NewIssue::new` and add
+/// optional fields with the builder methods.
+///
+/// The fields are stored in the same JSON form that the Jira REST API expects
+/// in the create request.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NewIssue {
+    fields: Map<String, Value>,
+    // Whether the issue type is a subtask type, if known.
+    subtask: Option<bool>,
+}
+
+impl NewIssue {
+    /// Prepare a new issue in the project, of the issue type with this name,
+    /// such as `Bug` or `Task`.
+    #[must_use]
+    pub fn new(project_key: &str, issue_type: &str, summary: &str) -> Self {
+        Self::default()
+            .field("project", json!({ "key": project_key }))
+            .field("issuetype", json!({ "name": issue_type }))
+            .field("summary", Value::from(summary))
+    }
+
+    /// Prepare a copy of an existing issue in the same project.
+    ///
+    /// The copy includes the summary, description, issue type, priority, labels, components,
+    /// and the custom fields that have a value and that are on the create screen,
+    /// according to `meta`. It excludes the fields that Jira manages by itself,
+    /// such as the status, the reporter, and the dates, and the custom fields that Jira
+    /// rejects on create, such as the rank or the sprint on most instances.
+    ///
+    /// Get `meta` from `JiraInstance::create_meta` for the project and the issue type
+    /// of the issue.
+    #[must_use]
+    pub fn from_issue(issue: &Issue, meta: &[CreateFieldMeta]) -> Self {
+        let fields = &issue.fields;
+
+        let mut new_issue = Self::default()
+            .field("project", json!({ "key": fields.project.key }))
+            .issue_type(&fields.issuetype)
+            .field("summary", Value::from(fields.summary.as_str()))
+            .labels(&fields.labels);
+
+        // Copy the description as it is: wiki markup on Jira Server, or ADF on Jira Cloud.
+        if let Some(description) = &fields.description {
+            new_issue = new_issue.field("description", json!(description));
+        }
+        if let Some(priority) = &fields.priority {
+            new_issue = new_issue.field("priority", json!({ "id": priority.id }));
+        }
+        if !fields.components.is_empty() {
+            let components: Vec<Value> = fields
+                .components
+                .iter()
+                .map(|component| json!({ "id": component.id }))
+                .collect();
+            new_issue = new_issue.field("components", Value::from(components));
+        }
+
+        if let Value::Object(extra) = &fields.extra {
+            for (id, value) in extra {
+                let editable = meta.iter().any(|field| &field.field_id == id);
+                if id.starts_with("customfield_") && editable && !value.is_null() {
+                    new_issue = new_issue.field(id, value.clone());
+                }
+            }
+        }
+
+        new_issue
+    }
+
+    /// Set the issue type from the issue type metadata, such as from `JiraInstance::issue_types`.
+    /// Unlike the name in `NewIssue::new`, the metadata also reveal
+    /// whether the type is a subtask type, which `NewIssue::parent` checks.
+    #[must_use]
+    pub fn issue_type(mut self, issue_type: &IssueType) -> Self {
+        self.subtask = Some(issue_type.subtask);
+        self.check_parent();
+        self.field("issuetype", json!({ "id": issue_type.id }))
+    }
+
+    /// Create the issue as a subtask of the parent issue with this key.
+    ///
+    /// A subtask needs a subtask issue type. If the issue type is known not to be
+    /// a subtask type, this logs a warning, because Jira Cloud also uses the parent
+    /// to link issues to epics in team-managed projects.
+    #[must_use]
+    pub fn parent(mut self, key: &str) -> Self {
+        self.fields
+            .insert("parent".to_string(), json!({ "key": key }));
+        self.check_parent();
+        self
+    }
+
+    /// Warn if the issue has a parent, but its issue type isn't a subtask type.
+    fn check_parent(&self) {
+        if let Some(parent) = self.parent_mismatch() {
+            log::warn!(
+                "The new issue has the parent {}, but its issue type isn't a subtask type.",
+                parent["key"]
+            );
+        }
+    }
+
+    /// The parent of the issue if its issue type is known not to be a subtask type.
+    fn parent_mismatch(&self) -> Option<&Value> {
+        if self.subtask == Some(false) {
+            self.fields.get("parent")
+        } else {
+            None
+        }
+    }
+
+    /// Set the description of the issue.
+    #[must_use]
+    pub fn description(self, description: &str) -> Self {
+        self.field("description", Value::from(description))
+    }
+
+    /// Set the description of the issue as rich text: an ADF document,
+    /// such as from `AdfBuilder::build`.
+    ///
+    /// `JiraInstance::create_issue` then creates the issue with the version 3 REST API,
+    /// which only Jira Cloud provides.
+    #[must_use]
+    pub fn description_adf(self, document: &Value) -> Self {
+        self.field("description", document.clone())
+    }
+
+    /// Whether the description is an ADF document rather than plain text or wiki markup.
+    pub(crate) fn has_adf_description(&self) -> bool {
+        self.fields
+            .get("description")
+            .map_or(false, Value::is_object)
+    }
+
+    /// Set the priority of the issue by its name, such as `Major`.
+    #[must_use]
+    pub fn priority(self, priority: &str) -> Self {
+        self.field("priority", json!({ "name": priority }))
+    }
+
+    /// Set the labels of the issue.
+    #[must_use]
+    pub fn labels(self, labels: &[String]) -> Self {
+        self.field("labels", Value::from(labels.to_vec()))
+    }
+
+    /// Set the components of the issue by their names.
+    #[must_use]
+    pub fn components(self, components: &[&str]) -> Self {
+        let components: Vec<Value> = components
+            .iter()
+            .map(|name| json!({ "name": name }))
+            .collect();
+        self.field("components", Value::from(components))
+    }
+
+    /// Set any field, including custom fields, by its ID. The value must be
+    /// in the form that the Jira REST API expects for this field.
+    #[must_use]
+    pub fn field(mut self, id: &str, value: Value) -> Self {
+        self.fields.insert(id.to_string(), value);
+        self
+    }
+
+    /// Set a date-time field, including custom fields, by its ID.
+    ///
+    /// Jira only accepts date-time input in its own format, such as `2023-01-10T09:00:00.000+0000`,
+    /// with no colon in the offset. The default RFC 3339 serialization of `chrono` has the colon.
+    #[must_use]
+    pub fn datetime_field<Tz>(self, id: &str, datetime: &DateTime<Tz>) -> Self
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        self.field(id, Value::from(jira_datetime::format(datetime)))
+    }
+
+    /// Remove a field from the new issue, so that Jira uses its default value.
+    #[must_use]
+    pub fn remove_field(mut self, id: &str) -> Self {
+        self.fields.remove(id);
+        self
+    }
+
+    /// The value of a field in the new issue, by its ID.
+    #[must_use]
+    pub fn get_field(&self, id: &str) -> Option<&Value> {
+        self.fields.get(id)
+    }
+
+    /// The JSON body of the request that creates this issue.
+    #[must_use]
+    pub fn create_body(&self) -> Value {
+        json!({ "fields": self.fields })
+    }
+
+    /// Check the new issue against the create screen fields of its project and issue type,
+    /// as listed by `JiraInstance::create_meta`, and list the problems that Jira would reject.
+    ///
+    /// The check finds required fields without a value, unless Jira has a default value
+    /// for them, and select values that aren't among the allowed values of the field.
+    /// An empty list means that the issue passes the check.
+    #[must_use]
+    pub fn validate(&self, meta: &[CreateFieldMeta]) -> Vec<FieldProblem> {
+        let mut problems = Vec::new();
+
+        for field in meta {
+            let value = self
+                .fields
+                .get(&field.field_id)
+                .filter(|value| !is_empty(value));
+
+            match (value, &field.allowed_values) {
+                (None, _) if field.required && !field.has_default_value => {
+                    problems.push(FieldProblem::MissingRequired {
+                        field: field.field_id.clone(),
+                        name: field.name.clone(),
+                    });
+                }
+                (Some(value), Some(allowed)) => {
+                    for item in select_items(value) {
+                        if !allowed.iter().any(|option| matches_option(item, option)) {
+                            problems.push(FieldProblem::DisallowedValue {
+                                field: field.field_id.clone(),
+                                name: field.name.clone(),
+                                value: item.clone(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        problems
+    }
+}
+
+/// A reason why Jira would reject a new issue, found by `NewIssue::validate`.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum FieldProblem {
+    #[error("The required field {name} ({field}) has no value.")]
+    MissingRequired { field: String, name: String },
+    #[error("The field {name} ({field}) doesn't allow the value {value}.")]
+    DisallowedValue {
+        field: String,
+        name: String,
+        value: Value,
+    },
+}
+
+/// Whether the value doesn't count as filled in: `null`, an empty string, or an empty list.
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.trim().is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+/// The selected options in the value of a select field, which can hold one or several.
+fn select_items(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        value => vec![value],
+    }
+}
+
+/// Whether the selected option refers to the allowed option. The selection identifies
+/// the option by any of its `id`, `name`, `key`, or `value` attributes, or by a plain string.
+fn matches_option(selected: &Value, option: &Value) -> bool {
+    match selected {
+        Value::Object(attributes) => {
+            !attributes.is_empty()
+                && attributes
+                    .iter()
+                    .all(|(name, value)| option.get(name) == Some(value))
+        }
+        Value::String(text) => ["id", "name", "key", "value"]
+            .iter()
+            .any(|name| option.get(name).and_then(Value::as_str) == Some(text)),
+        _ => false,
+    }
+}
+
+/// The reference to an issue that Jira has just created.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CreatedIssue {
+    pub id: String,
+    pub key: String,
+    #[serde(rename = "self")]
+    pub self_link: String,
+    #[serde(flatten)]
+    pub extra: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adf::Body;
+    use crate::issue_model::{Component, Fields, Priority, Project, Status};
+
+    /// Copy an issue and check that the create body only contains the editable fields.
+    #[test]
+    fn copy_issue() {
+        let issue = Issue {
+            key: "TEST-1".to_string(),
+            fields: Fields {
+                summary: "Original summary".to_string(),
+                description: Some(Body::Text("Original description".to_string())),
+                labels: vec!["backend".to_string()],
+                project: Project {
+                    key: "TEST".to_string(),
+                    ..Project::default()
+                },
+                issuetype: IssueType {
+                    id: "1".to_string(),
+                    name: "Bug".to_string(),
+                    ..IssueType::default()
+                },
+                priority: Some(Priority {
+                    id: "3".to_string(),
+                    ..Priority::default()
+                }),
+                components: vec![Component {
+                    id: "10200".to_string(),
+                    ..Component::default()
+                }],
+                status: Status {
+                    name: "In Progress".to_string(),
+                    ..Status::default()
+                },
+                extra: json!({
+                    "customfield_10100": "Custom text",
+                    "customfield_10200": null,
+                    // The rank, which isn't on the create screen.
+                    "customfield_10019": "0|i0001r:",
+                }),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+
+        let meta: Vec<CreateFieldMeta> = ["summary", "customfield_10100", "customfield_10200"]
+            .iter()
+            .map(|id| CreateFieldMeta {
+                field_id: (*id).to_string(),
+                ..CreateFieldMeta::default()
+            })
+            .collect();
+        let body = NewIssue::from_issue(&issue, &meta).create_body();
+        let fields = body["fields"].as_object().unwrap();
+
+        assert_eq!(fields["summary"], "Original summary");
+        assert_eq!(fields["description"], "Original description");
+        assert_eq!(fields["project"], json!({ "key": "TEST" }));
+        assert_eq!(fields["issuetype"], json!({ "id": "1" }));
+        assert_eq!(fields["priority"], json!({ "id": "3" }));
+        assert_eq!(fields["labels"], json!(["backend"]));
+        assert_eq!(fields["components"], json!([{ "id": "10200" }]));
+        assert_eq!(fields["customfield_10100"], "Custom text");
+
+        for read_only in [
+            "status",
+            "reporter",
+            "creator",
+            "created",
+            "updated",
+            "resolutiondate",
+            "customfield_10200",
+            "customfield_10019",
+        ] {
+            assert!(!fields.contains_key(read_only), "{read_only} was copied");
+        }
+    }
+
+    /// Report a missing required field and a priority outside the allowed values.
+    #[test]
+    fn validate_against_create_meta() {
+        let meta = vec![
+            CreateFieldMeta {
+                field_id: "summary".to_string(),
+                name: "Summary".to_string(),
+                required: true,
+                ..CreateFieldMeta::default()
+            },
+            CreateFieldMeta {
+                field_id: "customfield_10300".to_string(),
+                name: "Severity".to_string(),
+                required: true,
+                ..CreateFieldMeta::default()
+            },
+            CreateFieldMeta {
+                field_id: "reporter".to_string(),
+                name: "Reporter".to_string(),
+                required: true,
+                has_default_value: true,
+                ..CreateFieldMeta::default()
+            },
+            CreateFieldMeta {
+                field_id: "priority".to_string(),
+                name: "Priority".to_string(),
+                allowed_values: Some(vec![
+                    json!({ "id": "2", "name": "Critical" }),
+                    json!({ "id": "3", "name": "Major" }),
+                ]),
+                ..CreateFieldMeta::default()
+            },
+        ];
+
+        let valid = NewIssue::new("TEST", "Bug", "Crash")
+            .field("customfield_10300", json!({ "value": "High" }))
+            .priority("Major");
+        assert!(valid.validate(&meta).is_empty());
+
+        let invalid = NewIssue::new("TEST", "Bug", "Crash").priority("Trivial");
+        assert_eq!(
+            invalid.validate(&meta),
+            [
+                FieldProblem::MissingRequired {
+                    field: "customfield_10300".to_string(),
+                    name: "Severity".to_string(),
+                },
+                FieldProblem::DisallowedValue {
+                    field: "priority".to_string(),
+                    name: "Priority".to_string(),
+                    value: json!({ "name": "Trivial" }),
+                },
+            ]
+        );
+        assert_eq!(
+            invalid.validate(&meta)[0].to_string(),
+            "The required field Severity (customfield_10300) has no value."
+        );
+    }
+
+    /// Create a subtask with a parent issue.
+    #[test]
+    fn subtask_parent() {
+        let subtask_type = IssueType {
+            id: "5".to_string(),
+            name: "Sub-task".to_string(),
+            subtask: true,
+            ..IssueType::default()
+        };
+        let new_issue = NewIssue::new("TEST", "Sub-task", "A piece of work")
+            .issue_type(&subtask_type)
+            .parent("TEST-1");
+
+        let body = new_issue.create_body();
+        assert_eq!(body["fields"]["parent"], json!({ "key": "TEST-1" }));
+        assert_eq!(body["fields"]["issuetype"], json!({ "id": "5" }));
+        assert_eq!(new_issue.parent_mismatch(), None);
+    }
+
+    /// Detect a parent on an issue type that isn't a subtask type, in either order.
+    #[test]
+    fn parent_of_non_subtask() {
+        let story_type = IssueType {
+            id: "10001".to_string(),
+            name: "Story".to_string(),
+            subtask: false,
+            ..IssueType::default()
+        };
+
+        let parent_first = NewIssue::new("TEST", "Story", "A story")
+            .parent("EPIC-1")
+            .issue_type(&story_type);
+        assert_eq!(
+            parent_first.parent_mismatch(),
+            Some(&json!({ "key": "EPIC-1" }))
+        );
+
+        let type_first = NewIssue::new("TEST", "Story", "A story")
+            .issue_type(&story_type)
+            .parent("EPIC-1");
+        assert_eq!(
+            type_first.parent_mismatch(),
+            Some(&json!({ "key": "EPIC-1" }))
+        );
+
+        // Without a known issue type, a parent is no mismatch.
+        let unknown_type = NewIssue::new("TEST", "Story", "A story").parent("EPIC-1");
+        assert_eq!(unknown_type.parent_mismatch(), None);
+    }
+}
@@ -0,0 +1,83 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Follow the changes to an issue by polling it, for tools that can't receive webhooks.
+
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream};
+
+use crate::access::JiraInstance;
+use crate::errors::JiraQueryError;
+use crate::issue_model::{FieldChange, Issue};
+
+/// The progress of a watch on a single issue.
+struct Watch<'a> {
+    instance: &'a JiraInstance,
+    key: &'a str,
+    interval: Duration,
+    polled: bool,
+    previous: Option<Issue>,
+}
+
+impl JiraInstance {
+    /// Poll the issue on the interval and yield the changed fields whenever it changes,
+    /// compared to the previous download. Polls that find no change yield nothing.
+    ///
+    /// The first download only records the initial state of the issue.
+    /// The stream never ends by itself. Drop it to stop polling.
+    ///
+    /// A failed poll appears as an error item, and the polling continues
+    /// on the next interval, so that a temporary outage doesn't end the watch.
+    pub fn watch_issue_changes<'a>(
+        &'a self,
+        key: &'a str,
+        interval: Duration,
+    ) -> impl Stream<Item = Result<Vec<FieldChange>, JiraQueryError>> + 'a {
+        let watch = Watch {
+            instance: self,
+            key,
+            interval,
+            polled: false,
+            previous: None,
+        };
+
+        stream::unfold(watch, |mut watch| async move {
+            loop {
+                if watch.polled {
+                    tokio::time::sleep(watch.interval).await;
+                }
+                watch.polled = true;
+
+                let current = match watch.instance.issue(watch.key).await {
+                    Ok(issue) => issue,
+                    Err(error) => return Some((Err(error), watch)),
+                };
+
+                let changes = watch
+                    .previous
+                    .as_ref()
+                    .map(|previous| previous.diff(&current))
+                    .unwrap_or_default();
+                watch.previous = Some(current);
+
+                if !changes.is_empty() {
+                    return Some((Ok(changes), watch));
+                }
+            }
+        })
+    }
+}
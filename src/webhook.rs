@@ -0,0 +1,86 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! The payloads that Jira webhooks send, for applications that receive them.
+//!
+//! Deserialize the body of a webhook request into a `WebhookPayload`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::issue_model::{Comment, Issue, User, WebhookChangelog};
+
+/// The event that triggered a webhook.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub enum WebhookEvent {
+    IssueCreated,
+    IssueUpdated,
+    IssueDeleted,
+    CommentCreated,
+    CommentUpdated,
+    CommentDeleted,
+    /// Any other event, with its name as Jira reports it.
+    Other(String),
+}
+
+impl From<String> for WebhookEvent {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "jira:issue_created" => Self::IssueCreated,
+            "jira:issue_updated" => Self::IssueUpdated,
+            "jira:issue_deleted" => Self::IssueDeleted,
+            "comment_created" => Self::CommentCreated,
+            "comment_updated" => Self::CommentUpdated,
+            "comment_deleted" => Self::CommentDeleted,
+            _ => Self::Other(name),
+        }
+    }
+}
+
+impl From<WebhookEvent> for String {
+    fn from(event: WebhookEvent) -> Self {
+        match event {
+            WebhookEvent::IssueCreated => "jira:issue_created".to_string(),
+            WebhookEvent::IssueUpdated => "jira:issue_updated".to_string(),
+            WebhookEvent::IssueDeleted => "jira:issue_deleted".to_string(),
+            WebhookEvent::CommentCreated => "comment_created".to_string(),
+            WebhookEvent::CommentUpdated => "comment_updated".to_string(),
+            WebhookEvent::CommentDeleted => "comment_deleted".to_string(),
+            WebhookEvent::Other(name) => name,
+        }
+    }
+}
+
+/// The JSON body of a webhook request.
+///
+/// Which of the optional fields are present depends on the event.
+/// For example, only the comment events carry a comment,
+/// and only the `jira:issue_updated` event carries a changelog.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WebhookPayload {
+    #[serde(rename = "webhookEvent")]
+    pub webhook_event: WebhookEvent,
+    /// The time of the event in milliseconds since the Unix epoch.
+    pub timestamp: Option<i64>,
+    pub issue_event_type_name: Option<String>,
+    pub user: Option<User>,
+    pub issue: Option<Issue>,
+    pub changelog: Option<WebhookChangelog>,
+    pub comment: Option<Comment>,
+    #[serde(flatten)]
+    pub extra: Value,
+}
@@ -0,0 +1,567 @@
+/*
+Copyright 2022 Marek Suchánek <msuchane@redhat.com>
+
+Licensed under the Apache License, Version 2.0 (the "License");
+you may not use this file except in compliance with the License.
+You may obtain a copy of the License at
+
+    http://www.apache.org/licenses/LICENSE-2.0
+
+Unless required by applicable law or agreed to in writing, software
+distributed under the License is distributed on an "AS IS" BASIS,
+WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+See the License for the specific language governing permissions and
+limitations under the License.
+*/
+
+//! Methods that modify issues on the Jira instance,
+//! and the metadata that these modifications require.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::access::{parse_json, JiraInstance};
+use crate::errors::{ErrorBody, JiraQueryError};
+use crate::issue_model::{
+    Comment, CreateFieldMeta, CreateMetaPage, Issue, Resolution, Transition, Transitions, Version,
+    Visibility, Worklog,
+};
+use crate::jira_datetime;
+use crate::new_issue::{CreatedIssue, NewIssue};
+
+/// Work to log on an issue with `JiraInstance::add_worklog`.
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+pub struct NewWorklog {
+    /// When the work started. Jira requires its own date-time format,
+    /// which the request applies with `jira_datetime::serialize`.
+    #[serde(serialize_with = "jira_datetime::serialize")]
+    pub started: DateTime<Utc>,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A single change to the labels of an issue, which leaves the other labels intact.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LabelOperation {
+    Add(String),
+    Remove(String),
+}
+
+impl LabelOperation {
+    /// The JSON form of the operation in the `update` section of an edit request.
+    fn to_json(&self) -> Value {
+        match self {
+            Self::Add(label) => json!({ "add": label }),
+            Self::Remove(label) => json!({ "remove": label }),
+        }
+    }
+}
+
+/// Compute the minimal operations that change the `current` labels to the `desired` labels.
+/// The removals come first, followed by the additions, each in the order of the input.
+#[must_use]
+pub fn label_operations(current: &[String], desired: &[String]) -> Vec<LabelOperation> {
+    let removals = current
+        .iter()
+        .filter(|label| !desired.contains(label))
+        .map(|label| LabelOperation::Remove(label.clone()));
+    let additions = desired
+        .iter()
+        .filter(|label| !current.contains(label))
+        .map(|label| LabelOperation::Add(label.clone()));
+
+    let mut operations: Vec<LabelOperation> = Vec::new();
+    for operation in removals.chain(additions) {
+        // Skip labels listed twice in the input.
+        if !operations.contains(&operation) {
+            operations.push(operation);
+        }
+    }
+    operations
+}
+
+/// Distribute the unassigned issues evenly across the candidate users, in turn,
+/// such as to balance the load of a triage rotation. Issues that already have
+/// an assignee keep it and don't appear in the result.
+///
+/// Returns pairs of the issue key and the candidate that should take the issue.
+/// With no candidates, the result is empty.
+#[must_use]
+pub fn assign_round_robin(candidates: &[&str], issues: &[Issue]) -> Vec<(String, String)> {
+    issues
+        .iter()
+        .filter(|issue| issue.fields.assignee.is_none())
+        .zip(candidates.iter().cycle())
+        .map(|(issue, candidate)| (issue.key.clone(), (*candidate).to_string()))
+        .collect()
+}
+
+// The names of the transitions that resolve an issue in the default Jira workflows.
+const RESOLVING_TRANSITIONS: [&str; 2] = ["Done", "Resolve Issue"];
+
+impl JiraInstance {
+    /// Create a new issue and return the reference to it.
+    ///
+    /// If the description is an ADF document, such as from `NewIssue::description_adf`,
+    /// the request goes to the version 3 REST API, which only Jira Cloud provides.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the issue, such as when a required field is missing,
+    /// or if the request to Jira fails.
+    pub async fn create_issue(&self, issue: &NewIssue) -> Result<CreatedIssue, JiraQueryError> {
+        let url = if issue.has_adf_description() {
+            self.rest_v3_url("issue")
+        } else {
+            self.rest_url("issue")
+        };
+
+        let request = self
+            .authenticated_request(reqwest::Method::POST, &url)
+            .json(&issue.create_body());
+        let created = parse_json::<CreatedIssue>(self.send(request).await?).await?;
+
+        log::debug!("{created:#?}");
+
+        Ok(created)
+    }
+
+    /// List the fields on the create screen of the project and the issue type with this ID,
+    /// including whether each field is required and which values it accepts.
+    /// Check a new issue against the fields with `NewIssue::validate` before you create it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the field model.
+    pub async fn create_meta(
+        &self,
+        project_key: &str,
+        issue_type_id: &str,
+    ) -> Result<Vec<CreateFieldMeta>, JiraQueryError> {
+        let mut fields = Vec::new();
+
+        loop {
+            let url = self.rest_url(&format!(
+                "issue/createmeta/{project_key}/issuetypes/{issue_type_id}?startAt={}",
+                fields.len()
+            ));
+            let page = self.get_json::<CreateMetaPage>(&url).await?;
+
+            let last = page.is_last || page.values.is_empty();
+            fields.extend(page.values);
+            if last {
+                break;
+            }
+        }
+
+        log::debug!("{fields:#?}");
+
+        Ok(fields)
+    }
+
+    /// Add a comment to the issue and return the new comment.
+    ///
+    /// With a visibility, only the members of the group or the project role can see
+    /// the comment, such as for internal notes. The visibility type must be `group` or `role`,
+    /// and the value is the name of the group or the role.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidVisibility` if the visibility type is neither
+    /// `group` nor `role`, or if Jira rejects the comment or the request to Jira fails.
+    pub async fn add_comment(
+        &self,
+        key: &str,
+        body: &str,
+        visibility: Option<&Visibility>,
+    ) -> Result<Comment, JiraQueryError> {
+        let url = self.rest_url(&format!("issue/{key}/comment"));
+        self.post_comment(&url, Value::from(body), visibility).await
+    }
+
+    /// Add a comment with rich text to the issue and return the new comment.
+    /// The body is an ADF document, such as from `AdfBuilder::build`.
+    ///
+    /// The comment goes to the version 3 REST API, which only Jira Cloud provides.
+    /// The visibility works the same as with `JiraInstance::add_comment`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidVisibility` if the visibility type is neither
+    /// `group` nor `role`, or if Jira rejects the comment or the request to Jira fails.
+    pub async fn add_comment_adf(
+        &self,
+        key: &str,
+        body: &Value,
+        visibility: Option<&Visibility>,
+    ) -> Result<Comment, JiraQueryError> {
+        let url = self.rest_v3_url(&format!("issue/{key}/comment"));
+        self.post_comment(&url, body.clone(), visibility).await
+    }
+
+    /// Post a comment with the body and the optional visibility to the comment endpoint.
+    async fn post_comment(
+        &self,
+        url: &str,
+        body: Value,
+        visibility: Option<&Visibility>,
+    ) -> Result<Comment, JiraQueryError> {
+        let mut comment = json!({ "body": body });
+
+        if let Some(visibility) = visibility {
+            if !["group", "role"].contains(&visibility.r#type.as_str()) {
+                return Err(JiraQueryError::InvalidVisibility(visibility.r#type.clone()));
+            }
+            comment["visibility"] = json!({
+                "type": visibility.r#type,
+                "value": visibility.value,
+            });
+        }
+
+        let request = self
+            .authenticated_request(reqwest::Method::POST, url)
+            .json(&comment);
+        let created = parse_json::<Comment>(self.send(request).await?).await?;
+
+        Ok(created)
+    }
+
+    /// Log work on the issue and return the new worklog entry.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the worklog, such as when time tracking is disabled,
+    /// or if the request to Jira fails.
+    pub async fn add_worklog(
+        &self,
+        key: &str,
+        worklog: &NewWorklog,
+    ) -> Result<Worklog, JiraQueryError> {
+        let url = self.rest_url(&format!("issue/{key}/worklog"));
+        let request = self
+            .authenticated_request(reqwest::Method::POST, &url)
+            .json(worklog);
+        let created = parse_json::<Worklog>(self.send(request).await?).await?;
+
+        Ok(created)
+    }
+
+    /// Change the labels of the issue from `current` to `desired`.
+    ///
+    /// Rather than overwriting the labels, the method only sends the labels that you add
+    /// or remove, which preserves the labels that somebody else added in the meantime.
+    /// If the label sets are the same, the method makes no request.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the change or if the request to Jira fails.
+    pub async fn set_labels(
+        &self,
+        key: &str,
+        current: &[String],
+        desired: &[String],
+    ) -> Result<(), JiraQueryError> {
+        let operations = label_operations(current, desired);
+        self.update_labels(key, &operations).await
+    }
+
+    /// Apply the label operations to the issue.
+    /// If the list of operations is empty, the method makes no request.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the change or if the request to Jira fails.
+    pub async fn update_labels(
+        &self,
+        key: &str,
+        operations: &[LabelOperation],
+    ) -> Result<(), JiraQueryError> {
+        if operations.is_empty() {
+            return Ok(());
+        }
+
+        let url = self.rest_url(&format!("issue/{key}"));
+        let operations: Vec<Value> = operations.iter().map(LabelOperation::to_json).collect();
+        let body = json!({ "update": { "labels": operations } });
+
+        let request = self
+            .authenticated_request(reqwest::Method::PUT, &url)
+            .json(&body);
+        self.send(request).await?;
+
+        Ok(())
+    }
+
+    /// Set the fields of the issue, such as `json!({ "summary": "New summary" })`.
+    ///
+    /// With `last_seen`, which is the `updated` time of the issue as you last read it,
+    /// the method only writes the fields if nobody changed the issue since then.
+    /// Jira has no conditional update, so the method first reads the `updated` field
+    /// and compares it. A change that lands between the check and the update still
+    /// goes unnoticed, but the window is much shorter than the time since you read the issue.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::Conflict` if the issue changed after `last_seen`,
+    /// or if Jira rejects the change or the request to Jira fails.
+    pub async fn update_issue(
+        &self,
+        key: &str,
+        fields: &Value,
+        last_seen: Option<DateTime<Utc>>,
+    ) -> Result<(), JiraQueryError> {
+        if let Some(last_seen) = last_seen {
+            let current = self.issue_with_fields(key, &["updated"]).await?;
+            let updated = current.field::<DateTime<Utc>>("updated")?;
+
+            if let Some(updated) = updated.filter(|updated| *updated > last_seen) {
+                return Err(JiraQueryError::Conflict {
+                    key: key.to_string(),
+                    updated,
+                });
+            }
+        }
+
+        let url = self.rest_url(&format!("issue/{key}"));
+        let request = self
+            .authenticated_request(reqwest::Method::PUT, &url)
+            .json(&json!({ "fields": fields }));
+        self.send(request).await?;
+
+        Ok(())
+    }
+
+    /// List the workflow transitions that are currently available for the issue,
+    /// including the fields that you can set during each transition.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the transition model.
+    pub async fn transitions(&self, key: &str) -> Result<Vec<Transition>, JiraQueryError> {
+        let url = self.rest_url(&format!(
+            "issue/{key}/transitions?expand=transitions.fields"
+        ));
+
+        let transitions = self.get_json::<Transitions>(&url).await?;
+
+        log::debug!("{transitions:#?}");
+
+        Ok(transitions.transitions)
+    }
+
+    /// List all issue resolutions configured on the Jira instance.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the request to Jira fails or if the response doesn't match the resolution model.
+    pub async fn resolutions(&self) -> Result<Vec<Resolution>, JiraQueryError> {
+        let url = self.rest_url("resolution");
+
+        let resolutions = self.get_json::<Vec<Resolution>>(&url).await?;
+
+        Ok(resolutions)
+    }
+
+    /// Mark the version with this ID as released, such as when CI ships it,
+    /// and return the updated version.
+    ///
+    /// Without a release date, Jira keeps the current release date of the version.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the change or if the request to Jira fails.
+    pub async fn release_version(
+        &self,
+        version_id: &str,
+        release_date: Option<NaiveDate>,
+    ) -> Result<Version, JiraQueryError> {
+        let mut body = json!({ "released": true });
+        if let Some(date) = release_date {
+            body["releaseDate"] = Value::from(date.format("%Y-%m-%d").to_string());
+        }
+
+        self.update_version(version_id, &body).await
+    }
+
+    /// Archive the version with this ID and return the updated version.
+    ///
+    /// # Errors
+    ///
+    /// Fails if Jira rejects the change or if the request to Jira fails.
+    pub async fn archive_version(&self, version_id: &str) -> Result<Version, JiraQueryError> {
+        self.update_version(version_id, &json!({ "archived": true }))
+            .await
+    }
+
+    /// Edit the version with the fields in the body.
+    async fn update_version(
+        &self,
+        version_id: &str,
+        body: &Value,
+    ) -> Result<Version, JiraQueryError> {
+        let url = self.rest_url(&format!("version/{version_id}"));
+
+        let request = self
+            .authenticated_request(reqwest::Method::PUT, &url)
+            .json(body);
+        let version = parse_json::<Version>(self.send(request).await?).await?;
+
+        Ok(version)
+    }
+
+    /// Move the issue to a resolved status and set its resolution by name, such as `Done`.
+    ///
+    /// The method picks the `Done` or `Resolve Issue` transition, or else any transition
+    /// that leads to a status in the done category. If the transition doesn't offer
+    /// the resolution field, the issue is transitioned without setting the resolution.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the issue has no resolving transition, if the instance has no resolution
+    /// of this name, or if the request to Jira fails.
+    pub async fn resolve_issue(&self, key: &str, resolution: &str) -> Result<(), JiraQueryError> {
+        let transitions = self.transitions(key).await?;
+
+        let transition = transitions
+            .iter()
+            .find(|transition| {
+                RESOLVING_TRANSITIONS
+                    .iter()
+                    .any(|name| transition.name.eq_ignore_ascii_case(name))
+            })
+            .or_else(|| {
+                transitions
+                    .iter()
+                    .find(|transition| transition.to.status_category.key == "done")
+            })
+            .ok_or_else(|| JiraQueryError::MissingTransition {
+                issue: key.to_string(),
+            })?;
+
+        let fields = if transition.fields.contains_key("resolution") {
+            let resolution_id = self
+                .resolutions()
+                .await?
+                .into_iter()
+                .find(|candidate| candidate.name.eq_ignore_ascii_case(resolution))
+                .map(|candidate| candidate.id)
+                .ok_or_else(|| JiraQueryError::MissingResolution(resolution.to_string()))?;
+            Some(json!({ "resolution": { "id": resolution_id } }))
+        } else {
+            log::debug!(
+                "The {} transition has no resolution field. Skipping the resolution.",
+                transition.name
+            );
+            None
+        };
+
+        self.post_transition(key, &transition.id, fields).await
+    }
+
+    /// Move the issue to another status through the workflow transition with this ID.
+    /// List the available transitions with `JiraInstance::transitions`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with `JiraQueryError::InvalidTransition` if Jira rejects the transition,
+    /// such as when it isn't available from the current status of the issue,
+    /// or if the request to Jira fails.
+    pub async fn transition(&self, key: &str, transition_id: &str) -> Result<(), JiraQueryError> {
+        self.post_transition(key, transition_id, None).await
+    }
+
+    /// Perform a transition on the issue, optionally setting fields on the way.
+    async fn post_transition(
+        &self,
+        key: &str,
+        transition_id: &str,
+        fields: Option<Value>,
+    ) -> Result<(), JiraQueryError> {
+        let url = self.rest_url(&format!("issue/{key}/transitions"));
+
+        let mut body = json!({ "transition": { "id": transition_id } });
+        if let Some(fields) = fields {
+            body["fields"] = fields;
+        }
+
+        let request = self
+            .authenticated_request(reqwest::Method::POST, &url)
+            .json(&body);
+
+        match self.send(request).await {
+            Err(JiraQueryError::Http { status: 400, body }) => {
+                Err(JiraQueryError::InvalidTransition {
+                    issue: key.to_string(),
+                    transition: transition_id.to_string(),
+                    message: ErrorBody::explain(&body),
+                })
+            }
+            response => response.map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(ToString::to_string).collect()
+    }
+
+    /// Change `a, b` to `b, c`.
+    #[test]
+    fn compute_label_operations() {
+        let operations = label_operations(&labels(&["a", "b"]), &labels(&["b", "c"]));
+
+        assert_eq!(
+            operations,
+            vec![
+                LabelOperation::Remove("a".to_string()),
+                LabelOperation::Add("c".to_string())
+            ]
+        );
+        assert!(label_operations(&labels(&["a", "b"]), &labels(&["b", "a"])).is_empty());
+    }
+
+    /// Distribute 5 unassigned issues, and skip an assigned one, across 2 users.
+    #[test]
+    fn distribute_round_robin() {
+        use crate::issue_model::{Fields, User};
+
+        let issue = |key: &str, assigned: bool| Issue {
+            key: key.to_string(),
+            fields: Fields {
+                assignee: assigned.then(User::default),
+                ..Fields::default()
+            },
+            ..Issue::default()
+        };
+        let issues = vec![
+            issue("T-1", false),
+            issue("T-2", false),
+            issue("T-3", true),
+            issue("T-4", false),
+            issue("T-5", false),
+            issue("T-6", false),
+        ];
+
+        let assignments = assign_round_robin(&["alice", "bob"], &issues);
+        let expected: Vec<(String, String)> = [
+            ("T-1", "alice"),
+            ("T-2", "bob"),
+            ("T-4", "alice"),
+            ("T-5", "bob"),
+            ("T-6", "alice"),
+        ]
+        .iter()
+        .map(|(key, user)| ((*key).to_string(), (*user).to_string()))
+        .collect();
+        assert_eq!(assignments, expected);
+
+        assert!(assign_round_robin(&[], &issues).is_empty());
+    }
+}
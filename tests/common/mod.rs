@@ -0,0 +1,218 @@
+// Shared helpers for the offline tests. Not every test crate uses every helper.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use jira_query::JiraInstance;
+
+/// Load a JSON fixture from the `tests/fixtures` directory.
+pub fn fixture(name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+    let text = std::fs::read_to_string(&path).unwrap();
+    serde_json::from_str(&text).unwrap()
+}
+
+/// The JSON of a complete issue from the fixture, with the specified key.
+pub fn issue_json(key: &str) -> Value {
+    let mut issue = fixture("issue.json");
+    issue["key"] = Value::from(key);
+    issue
+}
+
+/// A page of JQL search results containing the issues with the specified keys.
+pub fn search_page(keys: &[&str], start_at: u32, max_results: u32, total: u32) -> Value {
+    let issues: Vec<Value> = keys.iter().map(|key| issue_json(key)).collect();
+    serde_json::json!({
+        "expand": "schema,names",
+        "startAt": start_at,
+        "maxResults": max_results,
+        "total": total,
+        "issues": issues,
+    })
+}
+
+/// A canned HTTP response served by the mock server.
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    /// A successful response with a JSON body.
+    pub fn json(body: &Value) -> Self {
+        Self::status(200, &body.to_string()).header("Content-Type", "application/json")
+    }
+
+    /// A response with the specified status and a plain body.
+    pub fn status(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    /// Add a header to the response.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// An HTTP request as received by the mock server.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// The value of a header, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The request body parsed as JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body).unwrap()
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> MockResponse + Send + Sync;
+
+/// A minimal HTTP server on localhost that answers every request
+/// using a handler and records the requests that it received.
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    /// Serve the responses in order. When only the last response remains,
+    /// the server keeps repeating it.
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        let queue = Mutex::new(responses);
+        Self::with_handler(move |_| {
+            let mut queue = queue.lock().unwrap();
+            if queue.len() > 1 {
+                queue.remove(0)
+            } else {
+                queue
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| MockResponse::status(404, ""))
+            }
+        })
+        .await
+    }
+
+    /// Answer every request using the handler function.
+    pub async fn with_handler<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = Arc::clone(&recorded);
+                let handler = Arc::clone(&handler);
+                tokio::spawn(async move {
+                    serve(stream, &recorded, handler.as_ref()).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// A `JiraInstance` connected to this server.
+    pub fn jira(&self) -> JiraInstance {
+        JiraInstance::at(self.url.clone()).unwrap()
+    }
+
+    /// All requests that the server has received so far.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Read a single request from the connection, answer it, and close the connection.
+async fn serve(mut stream: TcpStream, recorded: &Mutex<Vec<RecordedRequest>>, handler: &Handler) {
+    let mut buffer = Vec::new();
+    let mut chunk = [0_u8; 4096];
+
+    let header_end = loop {
+        let read = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let read = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => read,
+        };
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = RecordedRequest {
+        method,
+        path,
+        headers,
+        body: String::from_utf8_lossy(&body).to_string(),
+    };
+    let response = handler(&request);
+    recorded.lock().unwrap().push(request);
+
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&response.body).await;
+    let _ = stream.shutdown().await;
+}
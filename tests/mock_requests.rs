@@ -0,0 +1,1749 @@
+//! Offline tests that run the client against a local mock server.
+
+mod common;
+
+use common::{fixture, issue_json, search_page, MockResponse, MockServer};
+use jira_query::*;
+
+/// Simulate an issue that disappears from the results between two pages.
+/// The search should step back and still return every remaining issue exactly once.
+#[tokio::test]
+async fn search_with_shifting_total() {
+    let server = MockServer::start(vec![
+        // The first page, when all six issues exist.
+        MockResponse::json(&search_page(&["T-1", "T-2"], 0, 2, 6)),
+        // T-1 has been removed since the first page, so T-3 moved to position 1.
+        MockResponse::json(&search_page(&["T-4", "T-5"], 2, 2, 5)),
+        // The step back to position 1 returns T-3 and a duplicate T-4.
+        MockResponse::json(&search_page(&["T-3", "T-4"], 1, 2, 5)),
+        MockResponse::json(&search_page(&["T-5", "T-6"], 3, 2, 5)),
+        MockResponse::json(&search_page(&[], 5, 2, 5)),
+    ])
+    .await;
+
+    let instance = server.jira().paginate(Pagination::ChunkSize(2));
+    let issues = instance.search("project = T").await.unwrap();
+
+    let mut keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec!["T-1", "T-2", "T-3", "T-4", "T-5", "T-6"]);
+
+    let starts: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|request| request.path.split("startAt=").nth(1).unwrap().to_string())
+        .collect();
+    assert_eq!(starts, vec!["0", "2", "1", "3", "5"]);
+}
+
+/// Tune the connection pool and keep-alive, and check that the client still works.
+#[tokio::test]
+async fn tuned_http_client() {
+    let server = MockServer::start(vec![MockResponse::json(&issue_json("T-1"))]).await;
+
+    let instance = server
+        .jira()
+        .pool_max_idle_per_host(4)
+        .unwrap()
+        .tcp_keepalive(Some(std::time::Duration::from_secs(30)))
+        .unwrap();
+    let issue = instance.issue("T-1").await.unwrap();
+
+    assert_eq!(issue.key, "T-1");
+    assert_eq!(server.requests().len(), 1);
+
+    // The mock server only speaks HTTP/1.1, so only check that the client builds.
+    assert!(server.jira().http2_prior_knowledge().is_ok());
+}
+
+/// Send the default and a custom user agent.
+#[tokio::test]
+async fn custom_user_agent() {
+    let server = MockServer::start(vec![MockResponse::json(&issue_json("T-1"))]).await;
+
+    server.jira().issue("T-1").await.unwrap();
+    server
+        .jira()
+        .user_agent("release-tool/2.1")
+        .unwrap()
+        .issue("T-1")
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].header("User-Agent"),
+        Some(concat!("jira_query/", env!("CARGO_PKG_VERSION")))
+    );
+    assert_eq!(requests[1].header("User-Agent"), Some("release-tool/2.1"));
+}
+
+/// Send the requests with a client that the application configured,
+/// and refuse to replace it with the HTTP tuning methods.
+#[tokio::test]
+async fn shared_client() {
+    let server = MockServer::start(vec![MockResponse::json(&issue_json("T-1"))]).await;
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert("X-Service", "reports".parse().unwrap());
+    let client = reqwest::Client::builder()
+        .default_headers(headers)
+        .user_agent("shared-client/1.0")
+        .build()
+        .unwrap();
+
+    let shared = || JiraInstance::with_client(server.jira().host, client.clone());
+    shared().issue("T-1").await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].header("X-Service"), Some("reports"));
+    assert_eq!(requests[0].header("User-Agent"), Some("shared-client/1.0"));
+
+    let tuned = [
+        shared().user_agent("other/1.0"),
+        shared().pool_max_idle_per_host(4),
+        shared().tcp_keepalive(None),
+        shared().http2_prior_knowledge(),
+        shared().connect_timeout(std::time::Duration::from_secs(5)),
+    ];
+    for result in tuned {
+        assert!(matches!(result, Err(JiraQueryError::ExternalClient)));
+    }
+}
+
+/// Answer the requests that resolving an issue makes.
+/// Optionally remove the resolution field from the `Done` transition.
+async fn resolution_server(with_resolution_field: bool) -> MockServer {
+    MockServer::with_handler(move |request| {
+        if request.method == "POST" {
+            MockResponse::status(204, "")
+        } else if request.path.contains("/transitions") {
+            let mut transitions = fixture("transitions.json");
+            if !with_resolution_field {
+                transitions["transitions"][1]["fields"] = serde_json::json!({});
+            }
+            MockResponse::json(&transitions)
+        } else if request.path.ends_with("/resolution") {
+            MockResponse::json(&fixture("resolutions.json"))
+        } else {
+            MockResponse::status(404, "")
+        }
+    })
+    .await
+}
+
+/// Resolve an issue through the `Done` transition, which requires a resolution.
+#[tokio::test]
+async fn resolve_issue_with_resolution() {
+    let server = resolution_server(true).await;
+    server.jira().resolve_issue("T-1", "done").await.unwrap();
+
+    let requests = server.requests();
+    let post = requests.iter().find(|r| r.method == "POST").unwrap();
+    assert_eq!(post.path, "/rest/api/2/issue/T-1/transitions");
+    assert_eq!(
+        post.json(),
+        serde_json::json!({
+            "transition": { "id": "31" },
+            "fields": { "resolution": { "id": "10000" } }
+        })
+    );
+}
+
+/// Resolve an issue through a transition that offers no resolution field.
+#[tokio::test]
+async fn resolve_issue_without_resolution_field() {
+    let server = resolution_server(false).await;
+    server.jira().resolve_issue("T-1", "Done").await.unwrap();
+
+    let requests = server.requests();
+    assert!(!requests.iter().any(|r| r.path.ends_with("/resolution")));
+    let post = requests.iter().find(|r| r.method == "POST").unwrap();
+    assert_eq!(
+        post.json(),
+        serde_json::json!({ "transition": { "id": "31" } })
+    );
+}
+
+/// List the available transitions, and move the issue through one of them.
+#[tokio::test]
+async fn list_and_perform_transitions() {
+    let server = resolution_server(true).await;
+    let instance = server.jira();
+
+    let transitions = instance.transitions("T-1").await.unwrap();
+    let names: Vec<&str> = transitions.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["Start Progress", "Done"]);
+    assert_eq!(transitions[0].to.name, "In Progress");
+
+    instance.transition("T-1", "21").await.unwrap();
+
+    let requests = server.requests();
+    let post = requests.iter().find(|r| r.method == "POST").unwrap();
+    assert_eq!(post.path, "/rest/api/2/issue/T-1/transitions");
+    assert_eq!(
+        post.json(),
+        serde_json::json!({ "transition": { "id": "21" } })
+    );
+}
+
+/// Report a transition that isn't valid from the current status with the message from Jira.
+#[tokio::test]
+async fn invalid_transition() {
+    let server = MockServer::with_handler(|_| {
+        MockResponse::status(
+            400,
+            r#"{"errorMessages":["It seems that you have tried to perform a workflow operation (Done) that is not valid for the current state of this issue (T-1)."],"errors":{}}"#,
+        )
+    })
+    .await;
+
+    let result = server.jira().transition("T-1", "31").await;
+
+    match result {
+        Err(JiraQueryError::InvalidTransition {
+            issue,
+            transition,
+            message,
+        }) => {
+            assert_eq!(issue, "T-1");
+            assert_eq!(transition, "31");
+            assert!(message.starts_with("It seems that you have tried"));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+/// An unknown resolution name results in an error, without any transition.
+#[tokio::test]
+async fn resolve_issue_unknown_resolution() {
+    let server = resolution_server(true).await;
+    let result = server.jira().resolve_issue("T-1", "Fixed").await;
+
+    assert!(matches!(result, Err(JiraQueryError::MissingResolution(_))));
+    assert!(!server.requests().iter().any(|r| r.method == "POST"));
+}
+
+/// Export an issue using the columns of a saved filter.
+#[tokio::test]
+async fn export_filter_columns() {
+    let server = MockServer::start(vec![MockResponse::json(&fixture("filter_columns.json"))]).await;
+    let columns = server.jira().filter_columns("10400").await.unwrap();
+
+    assert_eq!(
+        server.requests()[0].path,
+        "/rest/api/2/filter/10400/columns"
+    );
+    assert_eq!(columns.len(), 7);
+    assert_eq!(columns[2].label, "Summary");
+
+    let issue: Issue = serde_json::from_value(issue_json("T-1")).unwrap();
+    let values = issue.column_values(&columns);
+
+    assert_eq!(values.len(), 7);
+    assert_eq!(values[0]["name"], "Bug");
+    assert_eq!(values[1], "T-1");
+    assert_eq!(values[2], "Service crashes on start");
+    assert_eq!(values[3]["displayName"], "Jane Doe");
+    assert_eq!(values[4], Value::Null);
+    assert_eq!(values[5], "Some custom text");
+    assert_eq!(values[6], Value::Null);
+}
+
+/// Create a new issue and check the request body.
+#[tokio::test]
+async fn create_issue() {
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({
+        "id": "10010",
+        "key": "T-10",
+        "self": "https://jira.example.com/rest/api/2/issue/10010"
+    }))])
+    .await;
+
+    let new_issue = NewIssue::new("T", "Bug", "Something broke")
+        .description("Steps to reproduce")
+        .labels(&["backend".to_string()]);
+    let created = server.jira().create_issue(&new_issue).await.unwrap();
+
+    assert_eq!(created.key, "T-10");
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/rest/api/2/issue");
+    assert_eq!(
+        request.json(),
+        serde_json::json!({
+            "fields": {
+                "project": { "key": "T" },
+                "issuetype": { "name": "Bug" },
+                "summary": "Something broke",
+                "description": "Steps to reproduce",
+                "labels": ["backend"]
+            }
+        })
+    );
+}
+
+/// Read the branches, commits, and pull requests from the development panel.
+#[tokio::test]
+async fn development_panel() {
+    let server = MockServer::with_handler(|request| {
+        let responses = fixture("dev_status.json");
+        let key = if request.path.contains("/summary") {
+            "summary"
+        } else {
+            request.path.rsplit("dataType=").next().unwrap()
+        };
+        MockResponse::json(&responses[key])
+    })
+    .await;
+
+    let info = server.jira().development_info("10001").await.unwrap();
+
+    assert_eq!(info.branches.len(), 1);
+    assert_eq!(info.branches[0].name, "feature/TEST-1-fix-crash");
+    assert_eq!(info.branches[0].repository.name, "example/service");
+    assert_eq!(info.commits.len(), 2);
+    assert_eq!(info.commits[0].display_id, "a1b2c3d");
+    assert_eq!(info.pull_requests.len(), 1);
+    assert_eq!(info.pull_requests[0].status, "OPEN");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(requests[1..]
+        .iter()
+        .all(|r| r.path.contains("issueId=10001&applicationType=github")));
+}
+
+/// Send only the label changes, not the complete list of labels.
+#[tokio::test]
+async fn set_labels_with_operations() {
+    let server = MockServer::start(vec![MockResponse::status(204, "")]).await;
+    let current = vec!["a".to_string(), "b".to_string()];
+    let desired = vec!["b".to_string(), "c".to_string()];
+
+    let instance = server.jira();
+    instance
+        .set_labels("T-1", &current, &desired)
+        .await
+        .unwrap();
+    // The same labels result in no request.
+    instance
+        .set_labels("T-1", &current, &current)
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "/rest/api/2/issue/T-1");
+    assert_eq!(
+        requests[0].json(),
+        serde_json::json!({ "update": { "labels": [{ "remove": "a" }, { "add": "c" }] } })
+    );
+}
+
+/// Measure the latency of a reachable instance, and fail on an error status.
+#[tokio::test]
+async fn ping_instance() {
+    let server = MockServer::start(vec![
+        MockResponse::json(&serde_json::json!({ "version": "9.4.0" })),
+        MockResponse::status(401, ""),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let latency = instance.ping().await.unwrap();
+    assert!(latency < std::time::Duration::from_secs(5));
+    assert_eq!(server.requests()[0].path, "/rest/api/2/serverInfo");
+
+    assert!(instance.ping().await.is_err());
+}
+
+/// Stop the search as soon as it has collected the requested number of issues.
+#[tokio::test]
+async fn search_first_n_stops_early() {
+    let server = MockServer::start(vec![
+        MockResponse::json(&search_page(&["T-1", "T-2"], 0, 2, 100)),
+        MockResponse::json(&search_page(&["T-3"], 2, 1, 100)),
+        MockResponse::json(&search_page(&["T-4", "T-5"], 3, 2, 100)),
+    ])
+    .await;
+
+    let instance = server.jira().paginate(Pagination::ChunkSize(2));
+    let issues = instance.search_first_n("project = T", 3).await.unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, vec!["T-1", "T-2", "T-3"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].path.ends_with("&maxResults=2&startAt=0"));
+    assert!(requests[1].path.ends_with("&maxResults=1&startAt=2"));
+}
+
+/// List the issue types of the instance and of a project.
+#[tokio::test]
+async fn list_issue_types() {
+    let server = MockServer::with_handler(|request| {
+        if request.path.ends_with("/issuetype") {
+            MockResponse::json(&fixture("issue_types.json"))
+        } else {
+            MockResponse::json(&fixture("project.json"))
+        }
+    })
+    .await;
+    let instance = server.jira();
+
+    let all_types = instance.issue_types().await.unwrap();
+    let names: Vec<&str> = all_types.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["Bug", "Task", "Sub-task"]);
+    assert!(all_types[2].subtask);
+    assert_eq!(all_types[2].description, "");
+
+    let project_types = instance.project_issue_types("TEST").await.unwrap();
+    assert_eq!(project_types.len(), 2);
+    assert_eq!(server.requests()[1].path, "/rest/api/2/project/TEST");
+}
+
+/// Map the 401 and 403 statuses to their distinct errors.
+#[tokio::test]
+async fn unauthorized_and_forbidden() {
+    let server = MockServer::start(vec![
+        MockResponse::status(401, ""),
+        MockResponse::status(403, ""),
+        MockResponse::status(500, ""),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let unauthorized = instance.issue("T-1").await.unwrap_err();
+    assert!(matches!(unauthorized, JiraQueryError::Unauthorized));
+
+    let forbidden = instance.issue("T-1").await.unwrap_err();
+    assert!(matches!(forbidden, JiraQueryError::Forbidden));
+
+    let other = instance.issue("T-1").await.unwrap_err();
+    assert!(matches!(other, JiraQueryError::Http { status: 500, .. }));
+}
+
+/// Check the watching status of several issues with a single narrow search.
+#[tokio::test]
+async fn watching_status_of_issues() {
+    let watches = |key: &str, watching: bool| {
+        serde_json::json!({
+            "id": "1",
+            "key": key,
+            "self": "https://jira.example.com/rest/api/2/issue/1",
+            "fields": {
+                "watches": {
+                    "self": format!("https://jira.example.com/rest/api/2/issue/{key}/watchers"),
+                    "watchCount": 1,
+                    "isWatching": watching
+                }
+            }
+        })
+    };
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({
+        "startAt": 0,
+        "maxResults": 2,
+        "total": 2,
+        "issues": [watches("T-1", true), watches("T-2", false)]
+    }))])
+    .await;
+
+    let status = server
+        .jira()
+        .watching_status(&["T-1", "T-2"])
+        .await
+        .unwrap();
+
+    assert_eq!(status.len(), 2);
+    assert!(status["T-1"]);
+    assert!(!status["T-2"]);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].path.contains("&fields=watches"));
+
+    // A key that would alter the query fails before any request.
+    let injected = "T-1) OR project = SECRET OR key in (T-3";
+    let result = server.jira().watching_status(&["T-2", injected]).await;
+    assert!(matches!(result, Err(JiraQueryError::InvalidKey(key)) if key == injected));
+    assert_eq!(server.requests().len(), 1);
+}
+
+/// Fetch a single comment, and report a missing comment clearly.
+#[tokio::test]
+async fn single_comment() {
+    let server = MockServer::with_handler(|request| {
+        if request.path.ends_with("/issue/TEST-1/comment/20001") {
+            MockResponse::json(&fixture("issue.json")["fields"]["comment"]["comments"][0])
+        } else {
+            MockResponse::status(
+                404,
+                r#"{"errorMessages":["Can not find a comment for the id: 99."]}"#,
+            )
+        }
+    })
+    .await;
+    let instance = server.jira();
+
+    let comment = instance.comment("TEST-1", "20001").await.unwrap();
+    assert_eq!(comment.id, "20001");
+    assert_eq!(
+        comment.body,
+        Body::Text("I can reproduce this on every start.".to_string())
+    );
+
+    let missing = instance.comment("TEST-1", "99").await;
+    assert!(matches!(
+        missing,
+        Err(JiraQueryError::MissingComment { issue, comment }) if issue == "TEST-1" && comment == "99"
+    ));
+}
+
+/// A POST that fails with a temporary error reaches the server only once,
+/// unless the instance opts in to repeat it.
+#[tokio::test]
+async fn post_not_retried() {
+    let unavailable =
+        || MockResponse::status(503, "Service Unavailable").header("Retry-After", "0");
+    let created = MockResponse::json(&serde_json::json!({
+        "id": "10010",
+        "key": "T-10",
+        "self": "https://jira.example.com/rest/api/2/issue/10010"
+    }));
+    let new_issue = NewIssue::new("T", "Bug", "Something broke");
+
+    let server = MockServer::start(vec![unavailable(), created.clone()]).await;
+    let result = server.jira().with_retries(3).create_issue(&new_issue).await;
+
+    assert!(matches!(
+        result,
+        Err(JiraQueryError::Http { status: 503, .. })
+    ));
+    assert_eq!(server.requests().len(), 1);
+
+    let server = MockServer::start(vec![unavailable(), created]).await;
+    let result = server
+        .jira()
+        .with_retries(3)
+        .retry_non_idempotent(true)
+        .create_issue(&new_issue)
+        .await;
+
+    assert_eq!(result.unwrap().key, "T-10");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].body, requests[1].body);
+}
+
+/// Request entity properties in a search and read them from the issues.
+#[tokio::test]
+async fn search_with_properties() {
+    let mut page = search_page(&["T-1"], 0, 50, 1);
+    page["issues"][0]["properties"] = fixture("issue_properties.json");
+    let server = MockServer::start(vec![MockResponse::json(&page)]).await;
+
+    let options =
+        SearchOptions::new().properties(&["com.example.release-tool", "com.example.triage"]);
+    let issues = server
+        .jira()
+        .search_with_options("project=T", &options)
+        .await
+        .unwrap();
+
+    assert!(server.requests()[0]
+        .path
+        .ends_with("&properties=com.example.release-tool,com.example.triage"));
+    let properties = issues[0].properties.as_ref().unwrap();
+    assert_eq!(properties["com.example.release-tool"]["train"], "2023.06");
+    assert_eq!(properties["com.example.triage"]["score"], 7);
+}
+
+/// The JSON of an issue with outward links to the issues with the specified keys.
+fn issue_linking_to(key: &str, linked_keys: &[&str]) -> serde_json::Value {
+    let mut issue = issue_json(key);
+    let template = issue["fields"]["issuelinks"][0].clone();
+    let links: Vec<serde_json::Value> = linked_keys
+        .iter()
+        .map(|linked_key| {
+            let mut link = template.clone();
+            link["outwardIssue"]["key"] = serde_json::Value::from(*linked_key);
+            link
+        })
+        .collect();
+    issue["fields"]["issuelinks"] = serde_json::Value::from(links);
+    issue
+}
+
+/// Follow the links of an issue through a graph with a cycle: A → B → C → A, and C → D.
+#[tokio::test]
+async fn issue_with_linked_issues() {
+    let graph = |key: &str| match key {
+        "A" => issue_linking_to("A", &["B"]),
+        "B" => issue_linking_to("B", &["C"]),
+        "C" => issue_linking_to("C", &["A", "D"]),
+        _ => issue_linking_to(key, &[]),
+    };
+    let server = MockServer::with_handler(move |request| {
+        if let Some(key) = request.path.strip_prefix("/rest/api/2/issue/") {
+            MockResponse::json(&graph(key))
+        } else {
+            // A batch search in the form of `id in (B,C)`.
+            let start = request.path.find('(').unwrap() + 1;
+            let end = request.path.find(')').unwrap();
+            let issues: Vec<serde_json::Value> =
+                request.path[start..end].split(',').map(graph).collect();
+            MockResponse::json(&serde_json::json!({
+                "startAt": 0,
+                "maxResults": 50,
+                "total": issues.len(),
+                "issues": issues,
+            }))
+        }
+    })
+    .await;
+    let instance = server.jira();
+
+    let two_hops = instance.issue_with_links("A", 2).await.unwrap();
+    let mut keys: Vec<&String> = two_hops.keys().collect();
+    keys.sort();
+    assert_eq!(keys, ["A", "B", "C"]);
+
+    let all = instance.issue_with_links("A", 10).await.unwrap();
+    assert_eq!(all.len(), 4);
+    assert!(all.contains_key("D"));
+
+    // The cycle back to A doesn't cause another download of A.
+    // The requests: A, B, C for two hops, and then A, B, C, D for all hops.
+    assert_eq!(server.requests().len(), 7);
+}
+
+/// Add an internal comment visible only to a project role, and reject an unknown visibility type.
+#[tokio::test]
+async fn add_comment_with_visibility() {
+    let comment = fixture("issue.json")["fields"]["comment"]["comments"][0].clone();
+    let server = MockServer::start(vec![MockResponse::json(&comment)]).await;
+    let instance = server.jira();
+
+    let visibility = Visibility {
+        r#type: "role".to_string(),
+        value: "Developers".to_string(),
+        extra: serde_json::Value::Null,
+    };
+    let created = instance
+        .add_comment("TEST-1", "Internal note", Some(&visibility))
+        .await
+        .unwrap();
+    assert_eq!(created.id, "20001");
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/rest/api/2/issue/TEST-1/comment");
+    assert_eq!(
+        request.json(),
+        serde_json::json!({
+            "body": "Internal note",
+            "visibility": { "type": "role", "value": "Developers" },
+        })
+    );
+
+    let invalid = Visibility {
+        r#type: "user".to_string(),
+        ..visibility
+    };
+    let result = instance
+        .add_comment("TEST-1", "Internal note", Some(&invalid))
+        .await;
+    assert!(matches!(result, Err(JiraQueryError::InvalidVisibility(kind)) if kind == "user"));
+    assert_eq!(server.requests().len(), 1);
+}
+
+/// Post rich text built with the ADF builder to the version 3 REST API,
+/// as a comment and as the description of a new issue.
+#[tokio::test]
+async fn send_adf_documents() {
+    let server = MockServer::start(vec![
+        MockResponse::json(&fixture("comment_cloud.json")),
+        MockResponse::json(&serde_json::json!({
+            "id": "10010",
+            "key": "T-10",
+            "self": "https://jira.example.com/rest/api/3/issue/10010"
+        })),
+    ])
+    .await;
+    let instance = server.jira();
+    let document = AdfBuilder::new()
+        .paragraph(vec![
+            AdfInline::text("The build is "),
+            AdfInline::text("broken").bold(),
+        ])
+        .build();
+
+    let visibility = Visibility {
+        r#type: "group".to_string(),
+        value: "developers".to_string(),
+        extra: serde_json::Value::Null,
+    };
+    let comment = instance
+        .add_comment_adf("T-1", &document, Some(&visibility))
+        .await
+        .unwrap();
+    assert!(matches!(comment.body, Body::Adf(_)));
+
+    let new_issue = NewIssue::new("T", "Bug", "Broken build").description_adf(&document);
+    let created = instance.create_issue(&new_issue).await.unwrap();
+    assert_eq!(created.key, "T-10");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/rest/api/3/issue/T-1/comment");
+    assert_eq!(
+        requests[0].json(),
+        serde_json::json!({
+            "body": document,
+            "visibility": { "type": "group", "value": "developers" },
+        })
+    );
+    assert_eq!(requests[1].path, "/rest/api/3/issue");
+    assert_eq!(requests[1].json()["fields"]["description"], document);
+}
+
+/// Log work with the start time in the date-time format that Jira accepts.
+#[tokio::test]
+async fn add_worklog_with_jira_datetime() {
+    use chrono::TimeZone;
+
+    let server = MockServer::with_handler(|request| {
+        // Like Jira, reject a start time with a colon in the offset.
+        if request.json()["started"]
+            .as_str()
+            .map_or(false, |started| started.ends_with("+0000"))
+        {
+            MockResponse::json(&fixture("worklogs.json")["worklogs"][0])
+        } else {
+            MockResponse::status(400, r#"{"errorMessages":["Invalid date format."]}"#)
+        }
+    })
+    .await;
+
+    let worklog = NewWorklog {
+        started: chrono::Utc.with_ymd_and_hms(2023, 1, 11, 8, 0, 0).unwrap(),
+        time_spent_seconds: 3600,
+        comment: Some("Reproduced the crash.".to_string()),
+    };
+    let created = server.jira().add_worklog("T-1", &worklog).await.unwrap();
+    assert_eq!(created.id, "40001");
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/rest/api/2/issue/T-1/worklog");
+    assert_eq!(
+        request.json(),
+        serde_json::json!({
+            "started": "2023-01-11T08:00:00.000+0000",
+            "timeSpentSeconds": 3600,
+            "comment": "Reproduced the crash.",
+        })
+    );
+}
+
+/// Space out concurrent requests under a low rate limit.
+#[tokio::test]
+async fn rate_limited_requests() {
+    let server = MockServer::start(vec![MockResponse::json(&issue_json("T-1"))]).await;
+    let instance = server.jira().rate_limit(10.0);
+
+    let start = std::time::Instant::now();
+    let (first, second, third) = tokio::join!(
+        instance.issue("T-1"),
+        instance.issue("T-1"),
+        instance.issue("T-1")
+    );
+    let elapsed = start.elapsed();
+
+    assert!(first.is_ok() && second.is_ok() && third.is_ok());
+    // At 10 requests per second, the third request starts 200 ms after the first.
+    assert!(
+        elapsed >= std::time::Duration::from_millis(200),
+        "{elapsed:?}"
+    );
+    assert_eq!(server.requests().len(), 3);
+}
+
+/// Release a version with a date, and archive a version.
+#[tokio::test]
+async fn release_and_archive_version() {
+    let mut version = fixture("issue.json")["fields"]["fixVersions"][0].clone();
+    version["released"] = serde_json::Value::from(true);
+    let server = MockServer::start(vec![MockResponse::json(&version)]).await;
+    let instance = server.jira();
+    let version_id = version["id"].as_str().unwrap();
+
+    let released = instance
+        .release_version(version_id, chrono::NaiveDate::from_ymd_opt(2023, 6, 30))
+        .await
+        .unwrap();
+    assert!(released.released);
+    instance.release_version(version_id, None).await.unwrap();
+    instance.archive_version(version_id).await.unwrap();
+
+    let requests = server.requests();
+    let path = format!("/rest/api/2/version/{version_id}");
+    assert!(requests
+        .iter()
+        .all(|request| request.method == "PUT" && request.path == path));
+    assert_eq!(
+        requests[0].json(),
+        serde_json::json!({ "released": true, "releaseDate": "2023-06-30" })
+    );
+    assert_eq!(requests[1].json(), serde_json::json!({ "released": true }));
+    assert_eq!(requests[2].json(), serde_json::json!({ "archived": true }));
+}
+
+/// Send a date-time field in the input format of Jira.
+#[tokio::test]
+async fn create_issue_with_datetime() {
+    use chrono::TimeZone;
+
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({
+        "id": "10011",
+        "key": "T-11",
+        "self": "https://jira.example.com/rest/api/2/issue/10011"
+    }))])
+    .await;
+
+    let started = chrono::Utc.with_ymd_and_hms(2023, 1, 10, 9, 0, 0).unwrap();
+    let new_issue =
+        NewIssue::new("T", "Task", "Plan the work").datetime_field("customfield_10300", &started);
+    let created = server.jira().create_issue(&new_issue).await.unwrap();
+
+    assert_eq!(created.key, "T-11");
+    assert_eq!(
+        server.requests()[0].json()["fields"]["customfield_10300"],
+        "2023-01-10T09:00:00.000+0000"
+    );
+}
+
+/// Answer searches with pages of the issues `T-1` to `T-5`.
+async fn five_issue_server() -> MockServer {
+    MockServer::with_handler(|request| {
+        let keys = ["T-1", "T-2", "T-3", "T-4", "T-5"];
+        let start_at: usize = request
+            .path
+            .split("startAt=")
+            .nth(1)
+            .and_then(|rest| rest.split('&').next())
+            .unwrap()
+            .parse()
+            .unwrap();
+        let end = keys.len().min(start_at + 2);
+        MockResponse::json(&search_page(&keys[start_at..end], start_at as u32, 2, 5))
+    })
+    .await
+}
+
+/// Export more issues than the memory limit, and read them back from disk.
+#[tokio::test]
+async fn export_spills_to_disk() {
+    let server = five_issue_server().await;
+    let instance = server.jira().paginate(Pagination::ChunkSize(2));
+
+    let export = instance.export("project=T", 3).await.unwrap();
+    assert_eq!(export.len(), 5);
+    let file = export.file().unwrap().to_path_buf();
+    assert!(file.exists());
+    // Other users can't read the exported issues.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    let keys: Vec<String> = export
+        .issues()
+        .unwrap()
+        .map(|issue| issue.unwrap().key)
+        .collect();
+    assert_eq!(keys, ["T-1", "T-2", "T-3", "T-4", "T-5"]);
+
+    drop(export);
+    assert!(!file.exists());
+
+    // Under the limit, the export stays in memory.
+    let in_memory = instance.export("project=T", 10).await.unwrap();
+    assert_eq!(in_memory.len(), 5);
+    assert!(in_memory.file().is_none());
+    assert_eq!(in_memory.issues().unwrap().count(), 5);
+}
+
+/// List the assignable users of a project and of an issue.
+#[tokio::test]
+async fn list_assignable_users() {
+    let server =
+        MockServer::start(vec![MockResponse::json(&fixture("assignable_users.json"))]).await;
+    let instance = server.jira();
+
+    let users = instance.assignable_users("TEST").await.unwrap();
+    assert_eq!(users.len(), 2);
+    assert_eq!(users[0].display_name, "Jane Doe");
+    instance.assignable_users("TEST-12").await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].path,
+        "/rest/api/2/user/assignable/search?project=TEST"
+    );
+    assert_eq!(
+        requests[1].path,
+        "/rest/api/2/user/assignable/search?issueKey=TEST-12"
+    );
+
+    // A key that would add its own query parameters never reaches Jira.
+    let error = instance
+        .assignable_users("TEST&maxResults=1000")
+        .await
+        .unwrap_err();
+    assert!(matches!(error, JiraQueryError::InvalidKey(key) if key == "TEST&maxResults=1000"));
+    assert_eq!(server.requests().len(), 2);
+}
+
+/// The JSON of an issue whose parent is the issue with the specified key.
+fn issue_with_parent(key: &str, parent_key: Option<&str>) -> serde_json::Value {
+    let mut issue = issue_json(key);
+    if let Some(parent_key) = parent_key {
+        let fields = &issue["fields"];
+        issue["fields"]["parent"] = serde_json::json!({
+            "id": "10100",
+            "key": parent_key,
+            "self": "https://jira.example.com/rest/api/2/issue/10100",
+            "fields": {
+                "summary": "The parent",
+                "status": fields["status"],
+                "priority": fields["priority"],
+                "issuetype": fields["issuetype"],
+            },
+        });
+    }
+    issue
+}
+
+/// Walk from a subtask through its story up to the epic.
+#[tokio::test]
+async fn ancestors_of_subtask() {
+    let server = MockServer::with_handler(|request| {
+        match request.path.trim_start_matches("/rest/api/2/issue/") {
+            "SUB-1" => MockResponse::json(&issue_with_parent("SUB-1", Some("STORY-1"))),
+            "STORY-1" => MockResponse::json(&issue_with_parent("STORY-1", Some("EPIC-1"))),
+            "EPIC-1" => MockResponse::json(&issue_with_parent("EPIC-1", None)),
+            // A subtask whose parent has been deleted.
+            "ORPHAN-1" => MockResponse::json(&issue_with_parent("ORPHAN-1", Some("GONE-1"))),
+            _ => MockResponse::status(404, ""),
+        }
+    })
+    .await;
+    let instance = server.jira();
+
+    let ancestors = instance.issue_ancestors("SUB-1").await.unwrap();
+    let keys: Vec<&str> = ancestors.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["STORY-1", "EPIC-1"]);
+
+    assert!(instance.issue_ancestors("EPIC-1").await.unwrap().is_empty());
+    assert!(instance
+        .issue_ancestors("ORPHAN-1")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+/// Distinguish a missing issue from a missing project by the 404 response body.
+#[tokio::test]
+async fn not_found_responses() {
+    let server =
+        MockServer::with_handler(|request| match request.path.rsplit('/').next().unwrap() {
+            "TEST-99" => MockResponse::status(
+                404,
+                r#"{"errorMessages":["Issue Does Not Exist"],"errors":{}}"#,
+            ),
+            "NOPE-1" | "NOPE" => MockResponse::status(
+                404,
+                r#"{"errorMessages":["No project could be found with key 'NOPE'."],"errors":{}}"#,
+            ),
+            _ => MockResponse::status(404, "<html>Not Found</html>"),
+        })
+        .await;
+    let instance = server.jira();
+
+    let missing_issue = instance.issue("TEST-99").await;
+    assert!(matches!(missing_issue, Err(JiraQueryError::NotFound { key }) if key == "TEST-99"));
+
+    let missing_project = instance.issue("NOPE-1").await;
+    assert!(
+        matches!(missing_project, Err(JiraQueryError::ProjectNotFound { key }) if key == "NOPE-1")
+    );
+    let missing_project = instance.project_issue_types("NOPE").await;
+    assert!(
+        matches!(missing_project, Err(JiraQueryError::ProjectNotFound { key }) if key == "NOPE")
+    );
+
+    // A body without messages, such as from a proxy, counts as a missing issue.
+    let unknown = instance.issue("OTHER-1").await;
+    assert!(matches!(unknown, Err(JiraQueryError::NotFound { key }) if key == "OTHER-1"));
+}
+
+/// Stream a search across several pages and collect the issues.
+#[tokio::test]
+async fn stream_search_pages() {
+    use futures_util::TryStreamExt;
+
+    let server = five_issue_server().await;
+    let instance = server.jira().paginate(Pagination::MaxResults(2));
+
+    let issues: Vec<Issue> = instance
+        .search_all("project=T")
+        .try_collect()
+        .await
+        .unwrap();
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["T-1", "T-2", "T-3", "T-4", "T-5"]);
+    // The last page is short, so the stream doesn't request another one.
+    assert_eq!(server.requests().len(), 3);
+}
+
+/// Step back in the stream when an issue disappears from the results between two pages,
+/// and yield every remaining issue exactly once.
+#[tokio::test]
+async fn stream_search_with_shifting_total() {
+    use futures_util::TryStreamExt;
+
+    let server = MockServer::start(vec![
+        MockResponse::json(&search_page(&["T-1", "T-2"], 0, 2, 6)),
+        // T-1 has been removed since the first page, so T-3 moved to position 1.
+        MockResponse::json(&search_page(&["T-4", "T-5"], 2, 2, 5)),
+        // The step back to position 1 returns T-3 and a duplicate T-4.
+        MockResponse::json(&search_page(&["T-3", "T-4"], 1, 2, 5)),
+        MockResponse::json(&search_page(&["T-5", "T-6"], 3, 2, 5)),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let issues: Vec<Issue> = instance
+        .search_all("project=T")
+        .try_collect()
+        .await
+        .unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["T-1", "T-2", "T-4", "T-5", "T-3", "T-6"]);
+    let starts: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|request| request.path.split("startAt=").nth(1).unwrap().to_string())
+        .collect();
+    assert_eq!(starts, vec!["0", "2", "1", "3"]);
+}
+
+/// A failed page ends the stream with an error item.
+#[tokio::test]
+async fn stream_search_error() {
+    use futures_util::StreamExt;
+
+    let server = MockServer::start(vec![
+        MockResponse::json(&search_page(&["T-1", "T-2"], 0, 2, 4)),
+        MockResponse::status(500, "Internal Server Error"),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let items: Vec<Result<Issue, JiraQueryError>> =
+        instance.search_all("project=T").collect().await;
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].as_ref().unwrap().key, "T-2");
+    assert!(items[2].is_err());
+}
+
+/// Download an icon as raw bytes, with authentication.
+#[tokio::test]
+async fn download_priority_icon() {
+    let png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0xFF];
+    let server = MockServer::start(vec![MockResponse {
+        status: 200,
+        headers: vec![("Content-Type".to_string(), "image/png".to_string())],
+        body: png.clone(),
+    }])
+    .await;
+    let instance = server
+        .jira()
+        .authenticate(Auth::ApiKey("secret".to_string()));
+
+    let url = format!("{}/images/icons/priorities/high.png", instance.host);
+    let icon = instance.download_icon(&url).await.unwrap();
+    assert_eq!(icon.as_ref(), png.as_slice());
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/images/icons/priorities/high.png");
+    assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+}
+
+/// Send no credentials with an icon from another server than the Jira instance.
+#[tokio::test]
+async fn download_foreign_icon() {
+    let server = MockServer::start(vec![MockResponse::status(200, "GIF89a")]).await;
+    let instance = server
+        .jira()
+        .authenticate(Auth::ApiKey("secret".to_string()));
+
+    // The same mock server under another host name is a different origin.
+    let foreign = instance.host.replace("127.0.0.1", "localhost");
+    let icon = instance
+        .download_icon(&format!("{foreign}/avatar/1234.gif"))
+        .await
+        .unwrap();
+    assert_eq!(icon.as_ref(), b"GIF89a");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/avatar/1234.gif");
+    assert_eq!(requests[0].header("authorization"), None);
+}
+
+/// Request only some fields of an issue and read them with their types.
+#[tokio::test]
+async fn issue_with_selected_fields() {
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({
+        "id": "10002",
+        "key": "TEST-2",
+        "self": "https://jira.example.com/rest/api/2/issue/10002",
+        "fields": {
+            "summary": "Fix the login page",
+            "status": fixture("issue.json")["fields"]["status"],
+        },
+    }))])
+    .await;
+    let instance = server.jira();
+
+    let issue = instance
+        .issue_with_fields("TEST-2", &["summary", "status", "labels"])
+        .await
+        .unwrap();
+    assert_eq!(issue.key, "TEST-2");
+    assert_eq!(
+        issue.field::<String>("summary").unwrap().as_deref(),
+        Some("Fix the login page")
+    );
+    assert!(issue.field::<Status>("status").unwrap().is_some());
+    assert_eq!(issue.field::<Vec<String>>("labels").unwrap(), None);
+
+    assert_eq!(
+        server.requests()[0].path,
+        "/rest/api/2/issue/TEST-2?fields=summary%2Cstatus%2Clabels"
+    );
+
+    // A field name can't add its own query parameters.
+    instance
+        .issue_with_fields("TEST-2", &["summary&expand=changelog"])
+        .await
+        .unwrap();
+    assert_eq!(
+        server.requests()[1].path,
+        "/rest/api/2/issue/TEST-2?fields=summary%26expand%3Dchangelog"
+    );
+
+    let error = instance
+        .issue_with_fields("TEST-2?expand=changelog", &["summary"])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, JiraQueryError::InvalidKey(_)));
+    assert_eq!(server.requests().len(), 2);
+}
+
+/// Poll an issue and report only the polls that find a change.
+#[tokio::test]
+async fn watch_issue_for_changes() {
+    use futures_util::StreamExt;
+    use std::time::Duration;
+
+    let mut renamed = issue_json("TEST-1");
+    renamed["fields"]["summary"] = serde_json::json!("A new summary");
+    let server = MockServer::start(vec![
+        MockResponse::json(&issue_json("TEST-1")),
+        MockResponse::json(&issue_json("TEST-1")),
+        MockResponse::json(&renamed),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let mut changes = Box::pin(instance.watch_issue_changes("TEST-1", Duration::from_millis(10)));
+    let first = changes.next().await.unwrap().unwrap();
+
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].field, "summary");
+    assert_eq!(first[0].to, "A new summary");
+    // The unchanged second poll yielded nothing.
+    assert_eq!(server.requests().len(), 3);
+}
+
+/// Download the create screen fields and catch a missing required field before creating.
+#[tokio::test]
+async fn validate_new_issue_with_create_meta() {
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({
+        "maxResults": 50,
+        "startAt": 0,
+        "total": 2,
+        "isLast": true,
+        "values": [
+            { "fieldId": "summary", "name": "Summary", "required": true, "hasDefaultValue": false },
+            {
+                "fieldId": "components",
+                "name": "Component/s",
+                "required": true,
+                "hasDefaultValue": false,
+                "allowedValues": [{ "id": "10200", "name": "Server" }],
+            },
+        ],
+    }))])
+    .await;
+    let instance = server.jira();
+
+    let meta = instance.create_meta("TEST", "1").await.unwrap();
+    let problems = NewIssue::new("TEST", "Bug", "Crash").validate(&meta);
+
+    assert_eq!(
+        problems,
+        [FieldProblem::MissingRequired {
+            field: "components".to_string(),
+            name: "Component/s".to_string(),
+        }]
+    );
+    assert_eq!(
+        server.requests()[0].path,
+        "/rest/api/2/issue/createmeta/TEST/issuetypes/1?startAt=0"
+    );
+}
+
+/// Download the content of an attachment through the authenticated client.
+#[tokio::test]
+async fn download_attachment_content() {
+    let server = MockServer::start(vec![MockResponse::status(200, "thread 'main' panicked")]).await;
+    let instance = server
+        .jira()
+        .authenticate(Auth::ApiKey("secret".to_string()));
+
+    let mut json = fixture("attachment.json");
+    json["content"] = format!("{}/secure/attachment/50001/crash.log", instance.host).into();
+    let attachment: Attachment = serde_json::from_value(json).unwrap();
+
+    let content = instance.download_attachment(&attachment).await.unwrap();
+    assert_eq!(content.as_ref(), b"thread 'main' panicked");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].path, "/secure/attachment/50001/crash.log");
+    assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+
+    // A content URL on another host gets no credentials.
+    let foreign = instance.host.replace("127.0.0.1", "localhost");
+    let mut json = fixture("attachment.json");
+    json["content"] = format!("{foreign}/secure/attachment/50001/crash.log").into();
+    let attachment: Attachment = serde_json::from_value(json).unwrap();
+
+    instance.download_attachment(&attachment).await.unwrap();
+    assert_eq!(server.requests()[1].header("authorization"), None);
+}
+
+/// Send the matching `Authorization` header for each authentication method.
+#[tokio::test]
+async fn authorization_headers() {
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({}))]).await;
+
+    let methods = vec![
+        (Auth::Anonymous, None),
+        (
+            Auth::ApiKey("personal-token".to_string()),
+            Some("Bearer personal-token"),
+        ),
+        (
+            Auth::Basic {
+                user: "jdoe".to_string(),
+                password: "secret".to_string(),
+            },
+            // The Base64 encoding of `jdoe:secret`.
+            Some("Basic amRvZTpzZWNyZXQ="),
+        ),
+    ];
+
+    for (auth, header) in methods {
+        server.jira().authenticate(auth).ping().await.unwrap();
+        let requests = server.requests();
+        assert_eq!(requests.last().unwrap().header("authorization"), header);
+    }
+}
+
+/// Send the token in a custom header instead of the `Authorization` header.
+#[tokio::test]
+async fn custom_auth_header() {
+    let server = MockServer::start(vec![MockResponse::json(&serde_json::json!({}))]).await;
+    let instance = server.jira().authenticate(Auth::Custom {
+        header_name: "X-Jira-Token".to_string(),
+        value: "gateway-token".to_string(),
+    });
+
+    instance.ping().await.unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].header("x-jira-token"), Some("gateway-token"));
+    assert_eq!(requests[0].header("authorization"), None);
+}
+
+/// Count the issues of a search by status across several pages.
+#[tokio::test]
+async fn count_issues_by_status() {
+    let mut first = search_page(&["T-1", "T-2"], 0, 2, 3);
+    first["issues"][1]["fields"]["status"]["name"] = "Open".into();
+    let server = MockServer::start(vec![
+        MockResponse::json(&first),
+        MockResponse::json(&search_page(&["T-3"], 2, 2, 3)),
+    ])
+    .await;
+    let instance = server.jira();
+
+    let counts = instance
+        .count_by("project = T", GroupField::Status)
+        .await
+        .unwrap();
+
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts["In Progress"], 2);
+    assert_eq!(counts["Open"], 1);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[0].path.ends_with("&startAt=0&fields=status"));
+    assert!(requests[1].path.contains("&startAt=2"));
+}
+
+/// Retry a request on 429 and 503 responses until it succeeds.
+#[tokio::test]
+async fn retry_transient_errors() {
+    let server = MockServer::start(vec![
+        MockResponse::status(429, "Too Many Requests").header("Retry-After", "0"),
+        MockResponse::status(503, "Service Unavailable"),
+        MockResponse::json(&issue_json("T-1")),
+    ])
+    .await;
+
+    let issue = server.jira().with_retries(2).issue("T-1").await.unwrap();
+
+    assert_eq!(issue.key, "T-1");
+    assert_eq!(server.requests().len(), 3);
+}
+
+/// Report the number of retries once they run out, and don't retry other errors.
+#[tokio::test]
+async fn retries_exhausted() {
+    let server = MockServer::start(vec![
+        MockResponse::status(503, "Service Unavailable").header("Retry-After", "0")
+    ])
+    .await;
+
+    let result = server.jira().with_retries(2).ping().await;
+    assert!(matches!(
+        result,
+        Err(JiraQueryError::RetriesExhausted {
+            retries: 2,
+            status: 503
+        })
+    ));
+    assert_eq!(server.requests().len(), 3);
+
+    let server = MockServer::start(vec![MockResponse::status(400, "Bad Request")]).await;
+    assert!(server.jira().with_retries(2).ping().await.is_err());
+    assert_eq!(server.requests().len(), 1);
+
+    // Without retries, a transient error fails like any other.
+    let server = MockServer::start(vec![MockResponse::status(503, "Service Unavailable")]).await;
+    let result = server.jira().ping().await;
+    assert!(matches!(
+        result,
+        Err(JiraQueryError::Http { status: 503, .. })
+    ));
+    assert_eq!(server.requests().len(), 1);
+}
+
+/// Return several issues in the requested order, and reject keys that could alter the query.
+#[tokio::test]
+async fn issues_in_requested_order() {
+    let server = MockServer::start(vec![MockResponse::json(&search_page(
+        &["T-1", "T-3", "T-2"],
+        0,
+        50,
+        3,
+    ))])
+    .await;
+    let instance = server.jira();
+
+    let issues = instance.issues(&["T-2", "T-3", "T-1"]).await.unwrap();
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["T-2", "T-3", "T-1"]);
+    assert!(server.requests()[0]
+        .path
+        .contains("jql=id%20in%20(T-2,T-3,T-1)"));
+
+    let result = instance
+        .issues(&["T-1", "T-2) OR project = SECRET OR key in (T-3"])
+        .await;
+    assert!(matches!(result, Err(JiraQueryError::InvalidKey(_))));
+    assert_eq!(server.requests().len(), 1);
+}
+
+/// Request more keys than a page holds in chunks of the page size, and keep every issue.
+#[tokio::test]
+async fn issues_beyond_a_page() {
+    let server = MockServer::with_handler(|request| {
+        let list = request.path.split("id%20in%20(").nth(1).unwrap();
+        let keys: Vec<&str> = list.split(')').next().unwrap().split(',').collect();
+        let count = keys.len() as u32;
+        MockResponse::json(&search_page(&keys, 0, 2, count))
+    })
+    .await;
+    let instance = server.jira().paginate(Pagination::MaxResults(2));
+
+    let keys = ["T-1", "T-2", "T-3", "T-4", "T-5"];
+    let issues = instance.issues(&keys).await.unwrap();
+
+    let found: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(found, keys);
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths.len(), 3);
+    assert!(paths[0].contains("(T-1,T-2)&maxResults=2"));
+    assert!(paths[2].contains("(T-5)&maxResults=2"));
+}
+
+/// Fail the whole request if one of the keys doesn't exist, as Jira rejects the search.
+#[tokio::test]
+async fn issues_with_missing_key() {
+    let server = MockServer::start(vec![MockResponse::status(
+        400,
+        r#"{"errorMessages":["An issue with key 'T-9' does not exist for field 'id'."],"errors":{}}"#,
+    )])
+    .await;
+
+    let result = server.jira().issues(&["T-1", "T-9"]).await;
+
+    assert!(matches!(
+        result,
+        Err(JiraQueryError::Http { status: 400, body }) if body.contains("T-9")
+    ));
+}
+
+/// Include the response body in the errors of an error status and of an unexpected shape.
+#[tokio::test]
+async fn error_details() {
+    let server = MockServer::start(vec![
+        MockResponse::status(
+            400,
+            r#"{"errorMessages":["The value 'X' does not exist."]}"#,
+        ),
+        MockResponse::json(&serde_json::json!({ "key": "T-1", "fields": "unexpected" })),
+    ])
+    .await;
+    let instance = server.jira();
+
+    match instance.search("project = X").await.unwrap_err() {
+        JiraQueryError::Http { status, body } => {
+            assert_eq!(status, 400);
+            assert!(body.contains("does not exist"));
+        }
+        error => panic!("Unexpected error: {error:?}"),
+    }
+
+    match instance.issue("T-1").await.unwrap_err() {
+        JiraQueryError::Deserialize { body, .. } => assert!(body.contains("unexpected")),
+        error => panic!("Unexpected error: {error:?}"),
+    }
+}
+
+/// Group the issues of the open sprints on a board, following the pages of each sprint.
+#[tokio::test]
+async fn group_issues_by_sprint() {
+    let server = MockServer::with_handler(|request| match request.path.as_str() {
+        "/rest/agile/1.0/board/5/sprint?state=active,future&startAt=0" => {
+            MockResponse::json(&fixture("sprints.json"))
+        }
+        "/rest/agile/1.0/board/5/sprint/37/issue?startAt=0" => {
+            MockResponse::json(&search_page(&["T-1", "T-2"], 0, 2, 3))
+        }
+        "/rest/agile/1.0/board/5/sprint/37/issue?startAt=2" => {
+            MockResponse::json(&search_page(&["T-3"], 2, 2, 3))
+        }
+        "/rest/agile/1.0/board/5/sprint/38/issue?startAt=0" => {
+            MockResponse::json(&search_page(&["T-4"], 0, 50, 1))
+        }
+        "/rest/agile/1.0/board/5/sprint/39/issue?startAt=0" => {
+            MockResponse::json(&search_page(&[], 0, 50, 0))
+        }
+        _ => MockResponse::status(404, ""),
+    })
+    .await;
+
+    let sprints = server.jira().issues_by_sprint(5).await.unwrap();
+
+    let keys = |sprint: i64| -> Vec<String> {
+        sprints[&sprint]
+            .iter()
+            .map(|issue| issue.key.clone())
+            .collect()
+    };
+    assert_eq!(sprints.len(), 3);
+    assert_eq!(keys(37), vec!["T-1", "T-2", "T-3"]);
+    assert_eq!(keys(38), vec!["T-4"]);
+    assert!(keys(39).is_empty());
+}
+
+/// Request a single issue with its change history.
+#[tokio::test]
+async fn issue_with_changelog() {
+    let mut issue = issue_json("TEST-1");
+    issue["changelog"] = fixture("changelog.json");
+    let server = MockServer::start(vec![MockResponse::json(&issue)]).await;
+
+    let issue = server.jira().issue_with_changelog("TEST-1").await.unwrap();
+
+    assert_eq!(issue.changelog.unwrap().histories.len(), 2);
+    assert_eq!(
+        server.requests()[0].path,
+        "/rest/api/2/issue/TEST-1?expand=changelog"
+    );
+}
+
+/// Replace a truncated changelog with the complete one before computing the cycle time,
+/// so that no status change is counted twice.
+#[tokio::test]
+async fn cycle_time_with_paginated_changelog() {
+    let changelog = fixture("workflow_changelog.json");
+    let histories = changelog["histories"].as_array().unwrap().clone();
+
+    let mut issue = issue_json("TEST-1");
+    issue["fields"]["resolutiondate"] = Value::from("2023-01-12T15:30:00.000+0000");
+    issue["changelog"] = changelog;
+    // The expansion only holds the three most recent entries.
+    issue["changelog"]["maxResults"] = Value::from(3);
+    issue["changelog"]["histories"] = Value::from(histories[2..].to_vec());
+    let page = |start_at: usize, end: usize| {
+        serde_json::json!({
+            "startAt": start_at,
+            "maxResults": 3,
+            "total": 5,
+            "isLast": end == histories.len(),
+            "values": histories[start_at..end].to_vec(),
+        })
+    };
+    let server = MockServer::start(vec![
+        MockResponse::json(&issue),
+        MockResponse::json(&page(0, 3)),
+        MockResponse::json(&page(3, 5)),
+    ])
+    .await;
+
+    let metrics = server.jira().cycle_time("TEST-1").await.unwrap();
+
+    assert_eq!(metrics.lead_time, Some(chrono::Duration::hours(54)));
+    assert_eq!(metrics.cycle_time, Some(chrono::Duration::hours(50)));
+    assert_eq!(
+        metrics.status_durations["Code Review"],
+        chrono::Duration::hours(10)
+    );
+    assert_eq!(
+        metrics.status_durations["In Progress"],
+        chrono::Duration::hours(40)
+    );
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/rest/api/2/issue/TEST-1?expand=changelog",
+            "/rest/api/2/issue/TEST-1/changelog?startAt=0",
+            "/rest/api/2/issue/TEST-1/changelog?startAt=3",
+        ]
+    );
+}
+
+/// Follow the pages of the comment endpoint until the total.
+#[tokio::test]
+async fn all_comments() {
+    let comment = |id: &str| {
+        let mut comment = fixture("comment_server.json");
+        comment["id"] = Value::from(id);
+        comment
+    };
+    let page = |ids: &[&str], start_at: u32| {
+        let comments: Vec<Value> = ids.iter().map(|id| comment(id)).collect();
+        serde_json::json!({
+            "comments": comments,
+            "maxResults": 2,
+            "startAt": start_at,
+            "total": 3,
+        })
+    };
+    let server = MockServer::start(vec![
+        MockResponse::json(&page(&["1", "2"], 0)),
+        MockResponse::json(&page(&["3"], 2)),
+    ])
+    .await;
+
+    let comments = server.jira().comments("TEST-1").await.unwrap();
+
+    let ids: Vec<&str> = comments.iter().map(|c| c.id.as_str()).collect();
+    assert_eq!(ids, vec!["1", "2", "3"]);
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(
+        paths,
+        vec![
+            "/rest/api/2/issue/TEST-1/comment?orderBy=created&startAt=0",
+            "/rest/api/2/issue/TEST-1/comment?orderBy=created&startAt=2",
+        ]
+    );
+}
+
+/// Record a search against the mock server, and replay it without the server.
+#[tokio::test]
+async fn record_and_replay_search() {
+    let dir = std::env::temp_dir().join(format!("jira_query-cassette-{}", std::process::id()));
+    let server = MockServer::start(vec![MockResponse::json(&search_page(
+        &["T-1", "T-2"],
+        0,
+        50,
+        2,
+    ))])
+    .await;
+
+    let recording = server.jira().record(RecordingTransport::new(&dir));
+    let recorded = recording.search("project = T").await.unwrap();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+    // Nothing listens on this port, so any request that isn't replayed fails.
+    let replay = JiraInstance::at("http://127.0.0.1:9".to_string())
+        .unwrap()
+        .replay(ReplayTransport::new(&dir));
+    let replayed = replay.search("project = T").await.unwrap();
+    assert_eq!(replayed, recorded);
+    assert_eq!(server.requests().len(), 1);
+
+    let missing = replay.search("project = OTHER").await;
+    assert!(matches!(
+        missing,
+        Err(JiraQueryError::MissingRecording { method, url })
+            if method == "GET" && url.contains("project%20=%20OTHER")
+    ));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Fail with a timeout error if the server accepts the connection but never responds.
+#[tokio::test]
+async fn request_timeout() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            // Keep the connection open without responding.
+            connections.push(stream);
+        }
+    });
+
+    let instance = JiraInstance::at(url)
+        .unwrap()
+        .with_timeout(std::time::Duration::from_millis(200));
+    let result = instance.issue("TEST-1").await;
+
+    assert!(matches!(result, Err(JiraQueryError::Timeout(_))));
+}
+
+/// Update an unchanged issue, and refuse to overwrite a concurrent edit.
+#[tokio::test]
+async fn update_issue_with_conflict_check() {
+    use chrono::TimeZone;
+
+    let server = MockServer::with_handler(|request| {
+        if request.method == "PUT" {
+            MockResponse::status(204, "")
+        } else {
+            MockResponse::json(&serde_json::json!({
+                "id": "10001",
+                "key": "TEST-1",
+                "self": "https://jira.example.com/rest/api/2/issue/10001",
+                "fields": { "updated": "2023-01-10T09:15:00.000+0000" },
+            }))
+        }
+    })
+    .await;
+    let instance = server.jira();
+    let fields = serde_json::json!({ "summary": "New summary" });
+    let read_at = |hour: u32| {
+        chrono::Utc
+            .with_ymd_and_hms(2023, 1, 10, hour, 15, 0)
+            .unwrap()
+    };
+
+    instance
+        .update_issue("TEST-1", &fields, Some(read_at(9)))
+        .await
+        .unwrap();
+
+    // Somebody edited the issue at 9:15, after it was read at 8:15.
+    let result = instance
+        .update_issue("TEST-1", &fields, Some(read_at(8)))
+        .await;
+    assert!(matches!(
+        result,
+        Err(JiraQueryError::Conflict { key, updated }) if key == "TEST-1" && updated == read_at(9)
+    ));
+
+    let requests = server.requests();
+    let methods: Vec<&str> = requests.iter().map(|r| r.method.as_str()).collect();
+    assert_eq!(methods, vec!["GET", "PUT", "GET"]);
+    assert_eq!(requests[0].path, "/rest/api/2/issue/TEST-1?fields=updated");
+    assert_eq!(
+        requests[1].json(),
+        serde_json::json!({ "fields": { "summary": "New summary" } })
+    );
+}
+
+/// Expand the field names and types of a search, and find a custom field by its name.
+#[tokio::test]
+async fn search_with_field_names() {
+    let mut page = search_page(&["T-1"], 0, 50, 1);
+    page["names"] = serde_json::json!({
+        "summary": "Summary",
+        "customfield_10016": "Story Points",
+        "customfield_10014": "Epic Link",
+    });
+    page["schema"] = serde_json::json!({
+        "summary": { "type": "string", "system": "summary" },
+        "customfield_10016": {
+            "type": "number",
+            "custom": "com.atlassian.jira.plugin.system.customfieldtypes:float",
+            "customId": 10016
+        },
+    });
+    let server = MockServer::start(vec![MockResponse::json(&page)]).await;
+
+    let options = SearchOptions::new().names(true).schema(true);
+    let results = server
+        .jira()
+        .search_page("project = T", &options, 0)
+        .await
+        .unwrap();
+
+    assert!(server.requests()[0]
+        .path
+        .ends_with("&startAt=0&expand=names,schema"));
+    assert_eq!(results.issues.len(), 1);
+    assert_eq!(results.field_id("story points"), Some("customfield_10016"));
+    assert_eq!(results.field_id("Sprint"), None);
+    let schema = results.schema.unwrap();
+    assert_eq!(schema["customfield_10016"].field_type, "number");
+    assert_eq!(schema["customfield_10016"].custom_id, Some(10016));
+    assert_eq!(schema["summary"].system.as_deref(), Some("summary"));
+}
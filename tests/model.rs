@@ -0,0 +1,570 @@
+//! Offline tests of the issue model against JSON fixtures.
+
+mod common;
+
+use common::{fixture, issue_json};
+use jira_query::*;
+
+/// Deserialize the issue from the standard fixture.
+fn issue() -> Issue {
+    serde_json::from_value(issue_json("TEST-1")).unwrap()
+}
+
+/// Check that the complete issue fixture deserializes.
+#[test]
+fn parse_issue_fixture() {
+    let issue: Issue = serde_json::from_value(fixture("issue.json")).unwrap();
+
+    assert_eq!(issue.key, "TEST-1");
+    assert_eq!(issue.fields.status.name, "In Progress");
+}
+
+/// Resolve nested paths through the typed fields.
+#[test]
+fn get_nested_path() {
+    let issue = issue();
+
+    assert_eq!(
+        issue.get_path("fields.status.name"),
+        Some(Value::from("In Progress"))
+    );
+    assert_eq!(
+        issue.get_path("fields.status.statusCategory.key"),
+        Some(Value::from("indeterminate"))
+    );
+    assert_eq!(
+        issue.get_path("fields.labels.1"),
+        Some(Value::from("team:core"))
+    );
+    assert_eq!(issue.get_path("key"), Some(Value::from("TEST-1")));
+}
+
+/// Resolve paths to custom fields and to missing fields.
+#[test]
+fn get_custom_field_path() {
+    let issue = issue();
+
+    assert_eq!(
+        issue.get_path("fields.customfield_10100"),
+        Some(Value::from("Some custom text"))
+    );
+    assert_eq!(
+        issue.get_path("fields.customfield_10200"),
+        Some(Value::Null)
+    );
+    assert_eq!(issue.get_path("fields.customfield_99999"), None);
+    assert_eq!(issue.get_path("fields.summary.value"), None);
+    assert_eq!(issue.get_path("fields.labels.5"), None);
+}
+
+/// Resolve nested paths inside a custom field with an object value.
+#[test]
+fn get_nested_custom_field_path() {
+    let mut json = issue_json("TEST-1");
+    json["fields"]["customfield_10300"] = serde_json::json!({
+        "value": "Team A",
+        "child": { "value": "Backend", "ids": [10, 20] },
+    });
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    assert_eq!(
+        issue.get_path("fields.customfield_10300.child.value"),
+        Some(Value::from("Backend"))
+    );
+    assert_eq!(
+        issue.get_path("fields.customfield_10300.child.ids.1"),
+        Some(Value::from(20))
+    );
+    assert_eq!(issue.get_path("fields.customfield_10300.child.ids.2"), None);
+    assert_eq!(issue.get_path("fields.customfield_10300.value.name"), None);
+}
+
+/// Read the request type and the participants of a Jira Service Management issue.
+#[test]
+fn jsm_request_fields() {
+    let mut json = issue_json("HELP-7");
+    for (id, value) in fixture("jsm_fields.json").as_object().unwrap() {
+        json["fields"][id] = value.clone();
+    }
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    let request_type = issue.fields.request_type("customfield_10010").unwrap();
+    assert_eq!(request_type.id, "12");
+    assert_eq!(request_type.name, "Get IT help");
+    assert_eq!(request_type.service_desk_id.as_deref(), Some("1"));
+
+    let participants = issue.fields.request_participants("customfield_10020");
+    assert_eq!(participants.len(), 1);
+    assert_eq!(participants[0].display_name, "Jane Doe");
+
+    // A regular issue has no request type.
+    assert!(issue.fields.request_type("customfield_10100").is_none());
+    assert!(issue
+        .fields
+        .request_participants("customfield_99999")
+        .is_empty());
+}
+
+/// Distinguish the visible fields from the hidden ones.
+#[test]
+fn field_visibility() {
+    let mut json = issue_json("TEST-1");
+    json["fields"]["customfield_10300"] = Value::Null;
+    let issue: Issue = serde_json::from_value(json).unwrap();
+    let fields = &issue.fields;
+
+    // Typed fields with a value.
+    assert!(fields.is_field_visible("summary"));
+    assert!(fields.is_field_visible("assignee"));
+    assert!(fields.is_field_visible("fixVersions"));
+    // A custom field with a value, and an empty custom field.
+    assert!(fields.is_field_visible("customfield_10100"));
+    assert!(fields.is_field_visible("customfield_10300"));
+    // A custom field that Jira didn't return.
+    assert!(!fields.is_field_visible("customfield_99999"));
+    // An empty optional typed field can't be told apart from a hidden one.
+    assert!(!fields.is_field_visible("duedate"));
+}
+
+/// Parse the full project object, including the lead and the description.
+#[test]
+fn project_with_lead() {
+    let project: Project = serde_json::from_value(fixture("project.json")).unwrap();
+
+    assert_eq!(project.key, "TEST");
+    assert_eq!(
+        project.description.as_deref(),
+        Some("A project for testing the client.")
+    );
+    assert_eq!(project.lead.unwrap().display_name, "Jane Doe");
+
+    // The reduced project in an issue has neither.
+    let issue: Issue = serde_json::from_value(issue_json("TEST-1")).unwrap();
+    assert!(issue.fields.project.lead.is_none());
+    assert!(issue.fields.project.description.is_none());
+}
+
+/// Key the fields by their display names from the field metadata.
+#[test]
+fn labeled_fields() {
+    let meta: Vec<FieldMeta> = serde_json::from_value(fixture("fields.json")).unwrap();
+    let issue: Issue = serde_json::from_value(issue_json("TEST-1")).unwrap();
+
+    let fields = issue.labeled_fields(&meta);
+
+    assert_eq!(fields["Summary"], "Service crashes on start");
+    assert_eq!(fields["Status"]["name"], "In Progress");
+    assert_eq!(fields["Customer"], "Some custom text");
+    // Fields without metadata keep their IDs.
+    assert_eq!(fields["customfield_10200"], Value::Null);
+    assert!(fields.contains_key("labels"));
+    assert!(!fields.contains_key("customfield_10100"));
+}
+
+/// Parse time tracking fields that exceed `i32` or that arrive as floats and strings.
+#[test]
+fn wide_time_fields() {
+    let mut json = issue_json("TEST-1");
+    let time_tracking = fixture("time_tracking.json");
+    for (field, value) in time_tracking.as_object().unwrap() {
+        json["fields"][field] = value.clone();
+    }
+    let issue: Issue = serde_json::from_value(json).unwrap();
+    let fields = &issue.fields;
+
+    assert_eq!(fields.timeestimate, Some(3_000_000_000));
+    assert_eq!(fields.aggregatetimeestimate, Some(3_000_000_000));
+    assert_eq!(fields.timeoriginalestimate, Some(3600));
+    assert_eq!(fields.timespent, Some(1800));
+    assert_eq!(fields.aggregatetimespent, Some(1800));
+    assert_eq!(fields.aggregatetimeoriginalestimate, None);
+    assert_eq!(fields.progress.total, 3_000_001_800);
+}
+
+/// Parse the payload of an issue update webhook, including the changelog.
+#[test]
+fn webhook_issue_updated() {
+    let payload: WebhookPayload =
+        serde_json::from_value(fixture("webhook_issue_updated.json")).unwrap();
+
+    assert_eq!(payload.webhook_event, WebhookEvent::IssueUpdated);
+    assert_eq!(payload.issue.unwrap().key, "TEST-1");
+    assert_eq!(payload.user.unwrap().name.as_deref(), Some("rroe"));
+    assert!(payload.comment.is_none());
+
+    let changelog = payload.changelog.unwrap();
+    assert_eq!(changelog.items.len(), 2);
+    assert_eq!(changelog.items[0].field, "status");
+    assert_eq!(changelog.items[0].from_string.as_deref(), Some("Open"));
+    assert_eq!(changelog.items[0].to_string.as_deref(), Some("In Progress"));
+}
+
+/// Parse the payload of a new comment webhook.
+#[test]
+fn webhook_comment_created() {
+    let payload: WebhookPayload =
+        serde_json::from_value(fixture("webhook_comment_created.json")).unwrap();
+
+    assert_eq!(payload.webhook_event, WebhookEvent::CommentCreated);
+    assert_eq!(payload.comment.unwrap().id, "20001");
+    assert!(payload.changelog.is_none());
+
+    let other: WebhookEvent = serde_json::from_value(Value::from("worklog_created")).unwrap();
+    assert_eq!(other, WebhookEvent::Other("worklog_created".to_string()));
+}
+
+/// Reduce a full issue to its condensed form.
+#[test]
+fn condense_issue() {
+    let issue = issue();
+    let condensed = CondensedIssue::from(&issue);
+
+    assert_eq!(condensed.key, "TEST-1");
+    assert_eq!(condensed.id, issue.id);
+    assert_eq!(condensed.self_link, issue.self_link);
+    assert_eq!(condensed.fields.summary, issue.fields.summary);
+    assert_eq!(condensed.fields.status, issue.fields.status);
+    assert_eq!(condensed.fields.priority, issue.fields.priority);
+
+    // The condensed form survives a round trip through JSON.
+    let json = serde_json::to_value(&condensed).unwrap();
+    assert_eq!(
+        serde_json::from_value::<CondensedIssue>(json).unwrap(),
+        condensed
+    );
+}
+
+/// Derive the browse URL of an issue and of its linked issue.
+#[test]
+fn browse_url() {
+    let issue = issue();
+
+    assert_eq!(
+        issue.browse_url().as_deref(),
+        Some("https://jira.example.com/browse/TEST-1")
+    );
+    let linked = issue.fields.issuelinks[0].outward_issue.as_ref().unwrap();
+    assert_eq!(
+        linked.browse_url().as_deref(),
+        Some("https://jira.example.com/browse/TEST-2")
+    );
+}
+
+/// Parse an imported issue without a reporter and a creator.
+#[test]
+fn missing_reporter_and_creator() {
+    let mut json = issue_json("TEST-1");
+    json["fields"]["reporter"] = Value::Null;
+    json["fields"]["creator"] = Value::Null;
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    assert!(issue.fields.reporter.is_none());
+    assert!(issue.fields.creator.is_none());
+
+    let complete = self::issue();
+    assert_eq!(
+        complete.fields.reporter.unwrap().display_name,
+        "Richard Roe"
+    );
+}
+
+/// Parse an archived issue with the archive date and the user who archived it.
+#[test]
+fn archived_issue() {
+    use chrono::TimeZone;
+
+    let issue: Issue = serde_json::from_value(fixture("archived_issue.json")).unwrap();
+
+    assert_eq!(
+        issue.fields.archiveddate,
+        Some(chrono::Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap())
+    );
+    assert_eq!(issue.fields.archivedby.unwrap().display_name, "Jane Doe");
+}
+
+/// Parse a due date without a time, and a resolution date with a time.
+#[test]
+fn due_and_resolution_dates() {
+    use chrono::{NaiveDate, TimeZone};
+
+    let mut json = issue_json("TEST-1");
+    json["fields"]["duedate"] = Value::from("2023-02-15");
+    json["fields"]["resolutiondate"] = Value::from("2023-02-10T16:45:12.000+0000");
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    assert_eq!(issue.fields.duedate, NaiveDate::from_ymd_opt(2023, 2, 15));
+    assert_eq!(
+        issue.fields.resolutiondate,
+        Some(
+            chrono::Utc
+                .with_ymd_and_hms(2023, 2, 10, 16, 45, 12)
+                .unwrap()
+        )
+    );
+}
+
+/// Read the raw and the ADF representations of a field.
+#[test]
+fn versioned_representations() {
+    let mut json = issue_json("TEST-1");
+    json["versionedRepresentations"] = fixture("versioned_representations.json");
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    assert_eq!(
+        issue.field_representation("description", "1"),
+        Some(&Value::from("The service *crashes* on startup."))
+    );
+    let adf = issue.field_representation("description", "2").unwrap();
+    assert_eq!(adf["type"], "doc");
+    assert_eq!(issue.field_representation("summary", "2"), None);
+    assert_eq!(issue.field_representation("labels", "1"), None);
+
+    assert_eq!(self::issue().field_representation("summary", "1"), None);
+}
+
+/// Deserialize custom fields into the types of the caller.
+#[test]
+fn typed_custom_fields() {
+    let fields = issue().fields;
+
+    assert_eq!(
+        fields.custom_field::<String>("customfield_10100").unwrap(),
+        Some("Some custom text".to_string())
+    );
+    assert_eq!(
+        fields.custom_field::<String>("customfield_10200").unwrap(),
+        None
+    );
+    assert_eq!(
+        fields.custom_field::<String>("customfield_99999").unwrap(),
+        None
+    );
+    assert!(fields.custom_field::<u32>("customfield_10100").is_err());
+}
+
+/// Serialize an issue back to JSON with the original Jira field names, and parse it again.
+#[test]
+fn issue_round_trip() {
+    let original = fixture("issue.json");
+    let issue: Issue = serde_json::from_value(original.clone()).unwrap();
+
+    let json = serde_json::to_value(&issue).unwrap();
+    assert_eq!(json["self"], original["self"]);
+    assert_eq!(
+        json["fields"]["issuelinks"][0]["type"],
+        original["fields"]["issuelinks"][0]["type"]
+    );
+    assert_eq!(json["fields"]["customfield_10100"], "Some custom text");
+    // Optional parts of the response that Jira didn't send stay absent.
+    assert!(json.get("renderedFields").is_none());
+
+    let parsed: Issue = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, issue);
+}
+
+/// Read the worklog entries of an issue, which are only present if requested.
+#[test]
+fn worklog_entries() {
+    assert!(issue().fields.worklog.is_none());
+
+    let mut json = issue_json("TEST-1");
+    json["fields"]["worklog"] = fixture("worklogs.json");
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    let worklog = issue.fields.worklog.unwrap();
+    assert_eq!(worklog.total, 2);
+    assert_eq!(worklog.worklogs[0].author.display_name, "Jane Doe");
+    assert_eq!(
+        worklog.worklogs[0].comment.as_deref(),
+        Some("Reproduced the crash.")
+    );
+    assert_eq!(worklog.worklogs[1].comment, None);
+    let seconds: i64 = worklog
+        .worklogs
+        .iter()
+        .map(|entry| entry.time_spent_seconds)
+        .sum();
+    assert_eq!(Some(seconds), issue.fields.timespent);
+}
+
+/// Read the attachments of an issue, which default to none.
+#[test]
+fn attachments() {
+    assert!(issue().fields.attachment.is_empty());
+
+    let mut json = issue_json("TEST-1");
+    json["fields"]["attachment"] = serde_json::json!([fixture("attachment.json")]);
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    let attachment = &issue.fields.attachment[0];
+    assert_eq!(attachment.filename, "crash.log");
+    assert_eq!(attachment.size, 2048);
+    assert_eq!(attachment.mime_type, "text/plain");
+    assert_eq!(attachment.author.display_name, "Richard Roe");
+    assert!(issue.fields.extra.get("attachment").is_none());
+}
+
+/// Parse a comment body in the wiki markup of Jira Server.
+#[test]
+fn server_comment_body() {
+    let comment: Comment = serde_json::from_value(fixture("comment_server.json")).unwrap();
+
+    assert_eq!(
+        comment.body,
+        Body::Text(
+            "I can reproduce this with *debug* logging:\n{code}\nPanic at startup\n{code}"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        comment.body.to_plain_text(),
+        "I can reproduce this with *debug* logging:\n{code}\nPanic at startup\n{code}"
+    );
+}
+
+/// Parse a comment body in the ADF of Jira Cloud, and an ADF issue description.
+#[test]
+fn cloud_comment_body() {
+    let comment: Comment = serde_json::from_value(fixture("comment_cloud.json")).unwrap();
+
+    assert!(matches!(comment.body, Body::Adf(_)));
+    assert_eq!(
+        comment.body.to_plain_text(),
+        "I can reproduce this with debug logging, see @Richard Roe:\n\
+         Panic at startup\n\
+         Fedora 37\n\
+         Fedora 38"
+    );
+
+    let mut json = issue_json("TEST-1");
+    json["fields"]["description"] = fixture("comment_cloud.json")["body"].clone();
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    assert_eq!(issue.fields.description, Some(comment.body));
+}
+
+/// Parse the change history of an issue, including a status change and a change without an author.
+#[test]
+fn issue_changelog() {
+    use chrono::TimeZone;
+
+    let mut json = issue_json("TEST-1");
+    json["changelog"] = fixture("changelog.json");
+    let expanded: Issue = serde_json::from_value(json).unwrap();
+
+    let changelog = expanded.changelog.unwrap();
+    assert_eq!(changelog.total, 2);
+
+    let status_change = &changelog.histories[0];
+    assert_eq!(
+        status_change.author.as_ref().unwrap().display_name,
+        "John Doe"
+    );
+    assert_eq!(
+        status_change.created,
+        chrono::Utc.with_ymd_and_hms(2023, 1, 11, 10, 0, 0).unwrap()
+    );
+    assert_eq!(status_change.items[0].field, "status");
+    assert_eq!(status_change.items[0].from.as_deref(), Some("1"));
+    assert_eq!(status_change.items[0].from_string.as_deref(), Some("Open"));
+    assert_eq!(status_change.items[0].to.as_deref(), Some("3"));
+    assert_eq!(
+        status_change.items[0].to_string.as_deref(),
+        Some("In Progress")
+    );
+
+    assert!(changelog.histories[1].author.is_none());
+
+    // Without the expansion, the issue has no changelog.
+    assert!(issue().changelog.is_none());
+}
+
+/// Map each avatar size to the field of the same size.
+#[test]
+fn avatar_sizes() {
+    let avatars = issue().fields.reporter.unwrap().avatar_urls;
+
+    assert!(avatars.xsmall.contains("size=xsmall"));
+    assert!(avatars.small.contains("size=small"));
+    assert!(avatars.medium.contains("size=medium"));
+    assert!(!avatars.large.contains("size="));
+}
+
+/// Read the sprints and story points from the agile custom fields that the instance configures.
+#[test]
+fn agile_fields() {
+    let mut json = issue_json("TEST-1");
+    json["fields"]["customfield_10104"] = serde_json::json!([{
+        "id": 37,
+        "name": "Sprint 12",
+        "state": "active",
+        "boardId": 5,
+        "goal": "Fix the startup crash",
+        "startDate": "2023-01-09T09:00:00.000Z",
+        "endDate": "2023-01-23T09:00:00.000Z"
+    }]);
+    json["fields"]["customfield_10106"] = serde_json::json!(5.0);
+    let issue: Issue = serde_json::from_value(json).unwrap();
+
+    let instance = JiraInstance::at("https://jira.example.com".to_string())
+        .unwrap()
+        .with_sprint_field("customfield_10104")
+        .with_story_points_field("customfield_10106");
+
+    let sprints = instance.sprints_of(&issue).unwrap();
+    assert_eq!(sprints.len(), 1);
+    assert_eq!(sprints[0].name, "Sprint 12");
+    assert_eq!(sprints[0].goal.as_deref(), Some("Fix the startup crash"));
+    assert_eq!(instance.story_points_of(&issue), Some(5.0));
+
+    // The default field IDs aren't in this issue.
+    let defaults = JiraInstance::at("https://jira.example.com".to_string()).unwrap();
+    assert!(defaults.sprints_of(&issue).unwrap().is_empty());
+    assert_eq!(defaults.story_points_of(&issue), None);
+}
+
+/// Compute the lead time, the cycle time, and the time in each status from the changelog.
+#[test]
+fn cycle_time_from_changelog() {
+    use chrono::{Duration, TimeZone, Utc};
+
+    let mut json = issue_json("TEST-1");
+    json["changelog"] = fixture("workflow_changelog.json");
+    json["fields"]["resolutiondate"] = Value::from("2023-01-12T15:30:00.000+0000");
+    let resolved: Issue = serde_json::from_value(json.clone()).unwrap();
+    let now = Utc.with_ymd_and_hms(2023, 2, 1, 0, 0, 0).unwrap();
+
+    let metrics = resolved.cycle_time(now);
+    assert_eq!(metrics.lead_time, Some(Duration::hours(54)));
+    // From the first move to In Progress, including the rework after the review.
+    assert_eq!(metrics.cycle_time, Some(Duration::hours(50)));
+    assert_eq!(metrics.status_durations["Open"], Duration::hours(4));
+    assert_eq!(metrics.status_durations["In Progress"], Duration::hours(40));
+    assert_eq!(metrics.status_durations["Code Review"], Duration::hours(10));
+    assert_eq!(metrics.status_durations["Done"], Duration::zero());
+    assert_eq!(metrics.status_durations.len(), 4);
+
+    // An unresolved issue stays in its last status until now.
+    json["fields"]["resolutiondate"] = Value::Null;
+    json["changelog"]["histories"]
+        .as_array_mut()
+        .unwrap()
+        .truncate(2);
+    let unresolved: Issue = serde_json::from_value(json).unwrap();
+
+    let metrics = unresolved.cycle_time(now);
+    assert_eq!(metrics.lead_time, None);
+    assert_eq!(metrics.cycle_time, None);
+    assert_eq!(
+        metrics.status_durations["Code Review"],
+        now - Utc.with_ymd_and_hms(2023, 1, 11, 13, 30, 0).unwrap()
+    );
+
+    // Without a changelog, the issue has been in its current status since the creation.
+    let metrics = issue().cycle_time(now);
+    assert_eq!(
+        metrics.status_durations["In Progress"],
+        now - issue().fields.created
+    );
+    assert_eq!(metrics.cycle_time, None);
+}